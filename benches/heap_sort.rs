@@ -0,0 +1,44 @@
+#![feature(test)]
+#![allow(non_snake_case)]
+
+extern crate test;
+
+use test::Bencher;
+use CLRS::{
+    ch06_Heapsort::{
+        dary_heap::dary_heap_sort,
+        heap_sort,
+    },
+    common,
+};
+
+const N: usize = 100_000;
+
+#[bench]
+fn binary_heap_sort(b: &mut Bencher) {
+    let A = common::random_vec::<u64>(N);
+    b.iter(|| {
+        let mut A = A.clone();
+        heap_sort(A.as_mut_slice());
+        A
+    });
+}
+
+macro_rules! bench_dary_heap_sort {
+    ($name:ident, $d:expr) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let A = common::random_vec::<u64>(N);
+            b.iter(|| {
+                let mut A = A.clone();
+                dary_heap_sort(A.as_mut_slice(), $d);
+                A
+            });
+        }
+    };
+}
+
+bench_dary_heap_sort!(dary_heap_sort_2, 2);
+bench_dary_heap_sort!(dary_heap_sort_3, 3);
+bench_dary_heap_sort!(dary_heap_sort_4, 4);
+bench_dary_heap_sort!(dary_heap_sort_8, 8);
@@ -0,0 +1,31 @@
+#![feature(test)]
+#![allow(non_snake_case)]
+
+extern crate test;
+
+use test::Bencher;
+use CLRS::{
+    ch08_Sorting_in_Linear_Time::radix_sort::radix_sort_with_width,
+    common,
+};
+
+const N: usize = 1_000_000;
+
+macro_rules! bench_radix_sort {
+    ($name:ident, $width:expr) => {
+        #[bench]
+        fn $name(b: &mut Bencher) {
+            let A = common::random_vec::<u64>(N);
+            b.iter(|| {
+                let mut A = A.clone();
+                radix_sort_with_width(A.as_mut_slice(), $width);
+                A
+            });
+        }
+    };
+}
+
+bench_radix_sort!(radix_sort_4_bits, 4);
+bench_radix_sort!(radix_sort_8_bits, 8);
+bench_radix_sort!(radix_sort_11_bits, 11);
+bench_radix_sort!(radix_sort_16_bits, 16);
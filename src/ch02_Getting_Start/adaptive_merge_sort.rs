@@ -0,0 +1,296 @@
+use super::{
+    insertion_sort::insertion_sort_impl,
+    search::gallop,
+};
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+const MIN_GALLOP: usize = 7;
+
+// Runs shorter than this are extended with insertion sort.  Chosen as in
+// TimSort, so that `n / min_run` is close to, but not above, a power of 2.
+fn min_run_length(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= 64 {
+        r |= n & 1;
+        n >>= 1;
+    }
+    n + r
+}
+
+// Length of the run starting at `A[0]`.  A strictly descending run is
+// reversed in place; requiring strictness keeps the sort stable.
+fn count_run<T, F>(A: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return A.len();
+    }
+
+    let mut end = 2;
+    if is_less(&A[1], &A[0]) {
+        while end < A.len() && is_less(&A[end], &A[end - 1]) {
+            end += 1;
+        }
+        A[..end].reverse();
+    } else {
+        while end < A.len() && !is_less(&A[end], &A[end - 1]) {
+            end += 1;
+        }
+    }
+    end
+}
+
+// Merges the sorted runs `A[..mid]` and `A[mid..]`, copying the left run
+// aside into `tmp`.  Switches to galloping when one run keeps winning.
+fn merge_runs<T, F>(A: &mut [T], mid: usize, tmp: &mut Vec<T>, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    // Elements of the left run not greater than the right run's head, and
+    // elements of the right run not less than the left run's tail, are
+    // already in place.
+    let start = gallop(&A[..mid], |x| !is_less(&A[mid], x));
+    let end = mid + gallop(&A[mid..], |x| is_less(x, &A[mid - 1]));
+    if start == mid || end == mid {
+        return;
+    }
+    let A = &mut A[start..end];
+    let mid = mid - start;
+
+    tmp.clear();
+    tmp.extend_from_slice(&A[..mid]);
+
+    // `k` never catches up with `j`, so writing `A[k]` cannot overwrite an
+    // element of the right run that is still to be merged.
+    let (mut i, mut j, mut k) = (0, mid, 0);
+    while i < tmp.len() && j < A.len() {
+        let (mut left_wins, mut right_wins) = (0, 0);
+        while i < tmp.len() && j < A.len() {
+            if is_less(&A[j], &tmp[i]) {
+                A[k] = A[j].clone();
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                A[k] = tmp[i].clone();
+                i += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+            k += 1;
+            if left_wins >= MIN_GALLOP || right_wins >= MIN_GALLOP {
+                break;
+            }
+        }
+
+        while i < tmp.len() && j < A.len() {
+            let count = gallop(&tmp[i..], |x| !is_less(&A[j], x));
+            A[k..k + count].clone_from_slice(&tmp[i..i + count]);
+            i += count;
+            k += count;
+            if i == tmp.len() {
+                break;
+            }
+
+            let count2 = gallop(&A[j..], |x| is_less(x, &tmp[i]));
+            for _ in 0..count2 {
+                A[k] = A[j].clone();
+                j += 1;
+                k += 1;
+            }
+
+            if count < MIN_GALLOP && count2 < MIN_GALLOP {
+                break;
+            }
+        }
+    }
+
+    let rest = tmp.len() - i;
+    A[k..k + rest].clone_from_slice(&tmp[i..]);
+}
+
+struct Run {
+    start: usize,
+    len: usize,
+}
+
+fn merge_at<T, F>(
+    A: &mut [T],
+    runs: &mut Vec<Run>,
+    n: usize,
+    tmp: &mut Vec<T>,
+    is_less: &mut F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let (start, mid) = (runs[n].start, runs[n].len);
+    let len = runs[n].len + runs[n + 1].len;
+    merge_runs(&mut A[start..start + len], mid, tmp, is_less);
+    runs[n].len = len;
+    runs.remove(n + 1);
+}
+
+// Restores the invariants on the lengths of the topmost runs:
+//   runs[n - 2].len > runs[n - 1].len + runs[n].len
+//   runs[n - 1].len > runs[n].len
+// so that the stack stays O(log n) deep and merges stay balanced.
+fn collapse<T, F>(
+    A: &mut [T],
+    runs: &mut Vec<Run>,
+    tmp: &mut Vec<T>,
+    is_less: &mut F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    while runs.len() > 1 {
+        let mut n = runs.len() - 2;
+        if n > 0 && runs[n - 1].len <= runs[n].len + runs[n + 1].len
+            || n > 1 && runs[n - 2].len <= runs[n - 1].len + runs[n].len
+        {
+            if runs[n - 1].len < runs[n + 1].len {
+                n -= 1;
+            }
+        } else if runs[n].len > runs[n + 1].len {
+            break;
+        }
+        merge_at(A, runs, n, tmp, is_less);
+    }
+}
+
+pub(crate) fn adaptive_merge_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let min_run = min_run_length(A.len());
+    let mut runs: Vec<Run> = Vec::new();
+    let mut tmp = Vec::new();
+
+    let mut start = 0;
+    while start < A.len() {
+        let mut len = count_run(&mut A[start..], is_less);
+        if len < min_run {
+            len = min_run.min(A.len() - start);
+            insertion_sort_impl(&mut A[start..start + len], is_less);
+        }
+        runs.push(Run {
+            start,
+            len,
+        });
+        collapse(A, &mut runs, &mut tmp, is_less);
+        start += len;
+    }
+
+    while runs.len() > 1 {
+        let n = runs.len() - 2;
+        merge_at(A, &mut runs, n, &mut tmp, is_less);
+    }
+}
+
+pub fn adaptive_merge_sort<T: PartialOrd + Clone>(A: &mut [T]) {
+    adaptive_merge_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn adaptive_merge_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    adaptive_merge_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn adaptive_merge_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    adaptive_merge_sort_impl(A, &mut compare::by_key(f));
+}
+
+mod tests {
+    #[test]
+    fn adaptive_merge_sort() {
+        use super::adaptive_merge_sort;
+        use crate::common;
+        for &n in &[1, 2, 63, 64, 65, 1000, 100_000] {
+            let mut A = common::random_vec::<f64>(n);
+            adaptive_merge_sort(A.as_mut_slice());
+            common::assert_asc(&A);
+        }
+    }
+
+    #[test]
+    fn adaptive_merge_sort_presorted() {
+        use super::adaptive_merge_sort_by;
+        use crate::common;
+        let n = 100_000;
+
+        let mut comparisons = 0;
+        let mut A: Vec<u32> = (0..n).collect();
+        adaptive_merge_sort_by(A.as_mut_slice(), |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        common::assert_asc(&A);
+        assert_eq!(comparisons, n - 1);
+
+        let mut comparisons = 0;
+        let mut A: Vec<u32> = (0..n).rev().collect();
+        adaptive_merge_sort_by(A.as_mut_slice(), |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        common::assert_asc(&A);
+        assert_eq!(comparisons, n - 1);
+    }
+
+    #[test]
+    fn adaptive_merge_sort_nearly_sorted() {
+        use super::adaptive_merge_sort_by;
+        use crate::common;
+        use rand::Rng;
+        let n = 100_000;
+        let mut rng = rand::thread_rng();
+
+        let mut A: Vec<u32> = (0..n).collect();
+        for _ in 0..10 {
+            let (i, j) = (rng.gen_range(0, n), rng.gen_range(0, n));
+            A.swap(i as usize, j as usize);
+        }
+        let mut comparisons = 0;
+        adaptive_merge_sort_by(A.as_mut_slice(), |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        common::assert_asc(&A);
+        assert!(comparisons < 4 * n);
+    }
+
+    #[test]
+    fn adaptive_merge_sort_by_key_stable() {
+        use super::adaptive_merge_sort_by_key;
+        use crate::common;
+        for &n_keys in &[2, 10, 1000] {
+            let mut A = common::random_records(10_000, n_keys);
+            adaptive_merge_sort_by_key(A.as_mut_slice(), |r| r.0);
+            common::assert_stable(&A);
+        }
+
+        let mut A: Vec<_> =
+            (0..10_000).map(|i| ((i / 100) as u32, i)).collect();
+        A.reverse();
+        adaptive_merge_sort_by_key(A.as_mut_slice(), |r| r.0);
+        let A: Vec<_> =
+            A.into_iter().map(|(key, i)| (key, 10_000 - i)).collect();
+        common::assert_stable(&A);
+    }
+}
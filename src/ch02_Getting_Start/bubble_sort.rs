@@ -1,15 +1,45 @@
-use std::cmp::PartialOrd;
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+pub(crate) fn bubble_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
 
-pub fn bubble_sort<T: PartialOrd>(A: &mut [T]) {
     for i in 0..A.len() - 1 {
         for j in (i + 1..A.len()).rev() {
-            if A[j] < A[j - 1] {
+            if is_less(&A[j], &A[j - 1]) {
                 A.swap(j, j - 1)
             }
         }
     }
 }
 
+pub fn bubble_sort<T: PartialOrd>(A: &mut [T]) {
+    bubble_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn bubble_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    bubble_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn bubble_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    bubble_sort_impl(A, &mut compare::by_key(f));
+}
+
 mod tests {
     #[test]
     fn bubble_sort() {
@@ -19,4 +49,22 @@ mod tests {
         bubble_sort(A.as_mut_slice());
         common::assert_asc(&A);
     }
+
+    #[test]
+    fn bubble_sort_by() {
+        use super::bubble_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(50);
+        bubble_sort_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn bubble_sort_by_key_stable() {
+        use super::bubble_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(100, 10);
+        bubble_sort_by_key(A.as_mut_slice(), |r| r.0);
+        common::assert_stable(&A);
+    }
 }
@@ -0,0 +1,504 @@
+use super::merge_sort::merge_sort_with_buffer_impl;
+use crate::{
+    ch06_Heapsort::{
+        heapify,
+        init_heap,
+    },
+    common::compare,
+};
+use std::cmp::PartialOrd;
+use std::convert::TryFrom;
+use std::env;
+use std::fs::{
+    self,
+    File,
+    OpenOptions,
+};
+use std::io::{
+    self,
+    BufReader,
+    BufWriter,
+    Read,
+    Write,
+};
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+// On disk, every record is its encoded payload prefixed by the payload
+// length as a little-endian `u32`.
+pub trait Record: Sized {
+    fn encode(&self, buf: &mut Vec<u8>);
+    fn decode(bytes: &[u8]) -> io::Result<Self>;
+}
+
+macro_rules! impl_record_primitive {
+    ($t:ty) => {
+        impl Record for $t {
+            fn encode(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn decode(bytes: &[u8]) -> io::Result<$t> {
+                let mut raw = [0u8; std::mem::size_of::<$t>()];
+                if bytes.len() != raw.len() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "record has wrong length",
+                    ));
+                }
+                raw.copy_from_slice(bytes);
+                Ok(<$t>::from_le_bytes(raw))
+            }
+        }
+    };
+}
+
+impl_record_primitive!(u8);
+impl_record_primitive!(u16);
+impl_record_primitive!(u32);
+impl_record_primitive!(u64);
+impl_record_primitive!(i8);
+impl_record_primitive!(i16);
+impl_record_primitive!(i32);
+impl_record_primitive!(i64);
+impl_record_primitive!(f32);
+impl_record_primitive!(f64);
+
+impl Record for Vec<u8> {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self);
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<Vec<u8>> {
+        Ok(bytes.to_vec())
+    }
+}
+
+impl Record for String {
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+
+    fn decode(bytes: &[u8]) -> io::Result<String> {
+        String::from_utf8(bytes.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// Returns the number of bytes written, or an `InvalidInput` error if the
+// encoded record does not fit the `u32` length.
+pub fn write_record<T: Record, W: Write>(
+    writer: &mut W,
+    record: &T,
+    buf: &mut Vec<u8>,
+) -> io::Result<usize> {
+    buf.clear();
+    record.encode(buf);
+    let len = u32::try_from(buf.len()).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "record longer than u32::MAX bytes",
+        )
+    })?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(buf)?;
+    Ok(4 + buf.len())
+}
+
+// Returns `None` at the end of the input.  An input ending inside a record,
+// even within its length, is an `UnexpectedEof` error.
+pub fn read_record<T: Record, R: Read>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+) -> io::Result<Option<T>> {
+    let mut len = [0u8; 4];
+    let mut filled = 0;
+    while filled < len.len() {
+        match reader.read(&mut len[filled..]) {
+            Ok(0) if filled == 0 => return Ok(None),
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "truncated record length",
+                ))
+            }
+            Ok(n) => filled += n,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    // The buffer only grows with the bytes actually read, so that a corrupt
+    // length cannot make it allocate up to 4 GiB at once.
+    let len = u64::from(u32::from_le_bytes(len));
+    buf.clear();
+    reader.by_ref().take(len).read_to_end(buf)?;
+    if (buf.len() as u64) < len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "truncated record",
+        ));
+    }
+    T::decode(buf).map(Some)
+}
+
+pub struct ExternalSortConfig {
+    // Approximate number of encoded bytes sorted in memory at once, though
+    // a run always holds at least one record.
+    pub memory_budget: usize,
+    // Maximum number of runs merged together in one pass.
+    pub fan_in: usize,
+    pub temp_dir: PathBuf,
+}
+
+impl Default for ExternalSortConfig {
+    fn default() -> ExternalSortConfig {
+        ExternalSortConfig {
+            memory_budget: 64 * 1024 * 1024,
+            fan_in: 16,
+            temp_dir: env::temp_dir(),
+        }
+    }
+}
+
+// A sorted run spilled to disk, removed once dropped.
+struct Run {
+    path: PathBuf,
+}
+
+impl Run {
+    fn create(
+        config: &ExternalSortConfig,
+    ) -> io::Result<(Run, BufWriter<File>)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        // The names are predictable, so never open a file that is already
+        // there (or a link planted in its place): take the next name instead.
+        loop {
+            let path = config.temp_dir.join(format!(
+                "clrs-external-sort-{}-{}.run",
+                process::id(),
+                COUNTER.fetch_add(1, Ordering::SeqCst),
+            ));
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => {
+                    return Ok((
+                        Run {
+                            path,
+                        },
+                        BufWriter::new(file),
+                    ))
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn open(&self) -> io::Result<BufReader<File>> {
+        Ok(BufReader::new(File::open(&self.path)?))
+    }
+}
+
+impl Drop for Run {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+fn spill_runs<T, R>(
+    input: &mut R,
+    config: &ExternalSortConfig,
+) -> io::Result<Vec<Run>>
+where
+    T: Record + PartialOrd + Clone,
+    R: Read,
+{
+    let mut runs = Vec::new();
+    let mut buf = Vec::new();
+    let mut chunk: Vec<T> = Vec::new();
+    let mut scratch = Vec::new();
+
+    let mut exhausted = false;
+    while !exhausted {
+        let mut size = 0;
+        chunk.clear();
+        // Every run takes at least one record, whatever the budget.
+        while chunk.is_empty() || size < config.memory_budget {
+            match read_record(input, &mut buf)? {
+                Some(record) => {
+                    size += 4 + buf.len();
+                    chunk.push(record);
+                }
+                None => {
+                    exhausted = true;
+                    break;
+                }
+            }
+        }
+        if chunk.is_empty() {
+            break;
+        }
+
+        merge_sort_with_buffer_impl(
+            &mut chunk,
+            &mut scratch,
+            &mut compare::by_partial_ord(),
+        );
+
+        let (run, mut writer) = Run::create(config)?;
+        for record in chunk.iter() {
+            write_record(&mut writer, record, &mut buf)?;
+        }
+        writer.flush()?;
+        runs.push(run);
+    }
+
+    Ok(runs)
+}
+
+// K-way merge of `runs` with a heap of their head records.  Ties go to the
+// run spilled first, which keeps the whole sort stable.
+fn merge_runs<T, W>(runs: &[Run], output: &mut W) -> io::Result<()>
+where
+    T: Record + PartialOrd,
+    W: Write,
+{
+    let mut buf = Vec::new();
+    let mut readers = Vec::with_capacity(runs.len());
+    let mut heap: Vec<(T, usize)> = Vec::with_capacity(runs.len());
+
+    for (i, run) in runs.iter().enumerate() {
+        let mut reader = run.open()?;
+        if let Some(record) = read_record(&mut reader, &mut buf)? {
+            heap.push((record, i));
+        }
+        readers.push(reader);
+    }
+
+    // The heap primitives of ch06 build max-heaps, so "less" here means
+    // "to be output later".
+    let mut is_less = compare::by_partial_ord();
+    let mut later = |a: &(T, usize), b: &(T, usize)| {
+        is_less(&b.0, &a.0) || !is_less(&a.0, &b.0) && b.1 < a.1
+    };
+
+    if heap.is_empty() {
+        return Ok(());
+    }
+    init_heap(&mut heap, &mut later);
+
+    while !heap.is_empty() {
+        write_record(output, &heap[0].0, &mut buf)?;
+        let i = heap[0].1;
+        match read_record(&mut readers[i], &mut buf)? {
+            Some(record) => heap[0] = (record, i),
+            None => {
+                let last = heap.len() - 1;
+                heap.swap(0, last);
+                heap.pop();
+            }
+        }
+        if !heap.is_empty() {
+            heapify(&mut heap, 0, &mut later);
+        }
+    }
+
+    Ok(())
+}
+
+pub fn external_merge_sort<T, R, W>(
+    mut input: R,
+    mut output: W,
+    config: &ExternalSortConfig,
+) -> io::Result<()>
+where
+    T: Record + PartialOrd + Clone,
+    R: Read,
+    W: Write,
+{
+    assert!(config.fan_in >= 2);
+
+    let mut runs = spill_runs::<T, R>(&mut input, config)?;
+
+    while runs.len() > config.fan_in {
+        let mut merged = Vec::with_capacity(runs.len() / config.fan_in + 1);
+        for group in runs.chunks(config.fan_in) {
+            let (run, mut writer) = Run::create(config)?;
+            merge_runs::<T, _>(group, &mut writer)?;
+            writer.flush()?;
+            merged.push(run);
+        }
+        runs = merged;
+    }
+
+    merge_runs::<T, _>(&runs, &mut output)?;
+    output.flush()
+}
+
+mod tests {
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "clrs-external-sort-test-{}-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    #[test]
+    fn external_merge_sort() {
+        use super::*;
+        use crate::common;
+        use std::fs::{
+            self,
+            File,
+        };
+        use std::io::{
+            BufReader,
+            BufWriter,
+            Write,
+        };
+
+        let (input_path, output_path) =
+            (temp_path("u64.in"), temp_path("u64.out"));
+
+        // 500k records of 12 bytes each: about 6MB on disk.
+        let A = common::random_vec::<u64>(500_000);
+        {
+            let mut writer = BufWriter::new(File::create(&input_path).unwrap());
+            let mut buf = Vec::new();
+            for x in A.iter() {
+                write_record(&mut writer, x, &mut buf).unwrap();
+            }
+            writer.flush().unwrap();
+        }
+        assert!(fs::metadata(&input_path).unwrap().len() > 5_000_000);
+
+        let config = ExternalSortConfig {
+            memory_budget: 256 * 1024,
+            fan_in: 4,
+            ..ExternalSortConfig::default()
+        };
+        external_merge_sort::<u64, _, _>(
+            BufReader::new(File::open(&input_path).unwrap()),
+            BufWriter::new(File::create(&output_path).unwrap()),
+            &config,
+        )
+        .unwrap();
+
+        let mut reader = BufReader::new(File::open(&output_path).unwrap());
+        let mut buf = Vec::new();
+        let mut B: Vec<u64> = Vec::with_capacity(A.len());
+        while let Some(x) = read_record(&mut reader, &mut buf).unwrap() {
+            B.push(x);
+        }
+        fs::remove_file(&input_path).unwrap();
+        fs::remove_file(&output_path).unwrap();
+
+        let mut A = A;
+        A.sort();
+        assert_eq!(A, B);
+    }
+
+    #[test]
+    fn external_merge_sort_strings() {
+        use super::*;
+
+        let A: Vec<String> =
+            (0..10_000).map(|i| format!("{}", (i * 7919) % 10_007)).collect();
+        let mut input = Vec::new();
+        let mut buf = Vec::new();
+        for x in A.iter() {
+            write_record(&mut input, x, &mut buf).unwrap();
+        }
+
+        let config = ExternalSortConfig {
+            memory_budget: 4096,
+            fan_in: 3,
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        external_merge_sort::<String, _, _>(&input[..], &mut output, &config)
+            .unwrap();
+
+        let mut reader = &output[..];
+        let mut B: Vec<String> = Vec::new();
+        while let Some(x) = read_record(&mut reader, &mut buf).unwrap() {
+            B.push(x);
+        }
+        let mut A = A;
+        A.sort();
+        assert_eq!(A, B);
+    }
+
+    #[test]
+    fn external_merge_sort_empty() {
+        use super::*;
+        let mut output = Vec::new();
+        let config = ExternalSortConfig::default();
+        external_merge_sort::<u32, _, _>(&[][..], &mut output, &config)
+            .unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn external_merge_sort_zero_budget() {
+        use super::*;
+        let mut input = Vec::new();
+        let mut buf = Vec::new();
+        for x in &[5u16, 3, 9, 1, 3] {
+            write_record(&mut input, x, &mut buf).unwrap();
+        }
+
+        let config = ExternalSortConfig {
+            memory_budget: 0,
+            fan_in: 2,
+            ..ExternalSortConfig::default()
+        };
+        let mut output = Vec::new();
+        external_merge_sort::<u16, _, _>(&input[..], &mut output, &config)
+            .unwrap();
+
+        let mut reader = &output[..];
+        let mut B = Vec::new();
+        while let Some(x) =
+            read_record::<u16, _>(&mut reader, &mut buf).unwrap()
+        {
+            B.push(x);
+        }
+        assert_eq!(B, vec![1, 3, 3, 5, 9]);
+    }
+
+    #[test]
+    fn read_record_truncated() {
+        use super::*;
+        let mut buf = Vec::new();
+        let mut input = Vec::new();
+        write_record(&mut input, &7u32, &mut buf).unwrap();
+
+        let mut reader = &input[..];
+        assert_eq!(
+            read_record::<u32, _>(&mut reader, &mut buf).unwrap(),
+            Some(7)
+        );
+        assert_eq!(read_record::<u32, _>(&mut reader, &mut buf).unwrap(), None);
+
+        // Cut within the length, then within the payload.
+        for &end in &[2, 6] {
+            let mut reader = &input[..end];
+            let e = read_record::<u32, _>(&mut reader, &mut buf).unwrap_err();
+            assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        }
+
+        // A corrupt length far beyond the input.
+        let input = [0xf0, 0xff, 0xff, 0xff, 1, 2];
+        let mut buf = Vec::new();
+        let e =
+            read_record::<Vec<u8>, _>(&mut &input[..], &mut buf).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(buf.capacity() < 1 << 20);
+    }
+}
@@ -1,11 +1,19 @@
-use std::cmp::PartialOrd;
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
 
 #[inline]
-pub fn insertion_sort<T: PartialOrd + Clone>(A: &mut [T]) {
+pub(crate) fn insertion_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
     for j in 1..A.len() {
         let mut i: isize = j as isize - 1;
         let key = A[j].clone();
-        while i >= 0 && A[i as usize] > key {
+        while i >= 0 && is_less(&key, &A[i as usize]) {
             A[(i + 1) as usize] = A[i as usize].clone();
             i -= 1;
         }
@@ -13,6 +21,44 @@ pub fn insertion_sort<T: PartialOrd + Clone>(A: &mut [T]) {
     }
 }
 
+// Variant for callers that cannot require `T: Clone`: the key is carried
+// along by swapping instead of being held aside.
+#[inline]
+pub(crate) fn insertion_sort_by_swap_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for j in 1..A.len() {
+        let mut i = j;
+        while i > 0 && is_less(&A[i], &A[i - 1]) {
+            A.swap(i, i - 1);
+            i -= 1;
+        }
+    }
+}
+
+#[inline]
+pub fn insertion_sort<T: PartialOrd + Clone>(A: &mut [T]) {
+    insertion_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn insertion_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    insertion_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn insertion_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    insertion_sort_impl(A, &mut compare::by_key(f));
+}
+
 mod tests {
     #[test]
     fn insertion_sort() {
@@ -22,4 +68,22 @@ mod tests {
         insertion_sort(A.as_mut_slice());
         common::assert_asc(&A);
     }
+
+    #[test]
+    fn insertion_sort_by() {
+        use super::insertion_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(100);
+        insertion_sort_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn insertion_sort_by_key_stable() {
+        use super::insertion_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(100, 10);
+        insertion_sort_by_key(A.as_mut_slice(), |r| r.0);
+        common::assert_stable(&A);
+    }
 }
@@ -1,31 +1,402 @@
-use std::cmp::PartialOrd;
+use super::insertion_sort::insertion_sort_by_swap_impl;
+use crate::common::compare;
+use std::{
+    cmp::{
+        Ordering,
+        PartialOrd,
+    },
+    collections::HashMap,
+    hash::Hash,
+};
+
+const IN_PLACE_INSERTION_SORT_THRESHOLD: usize = 12;
+
+// Returns the number of inversions between `A` and `B`, that is the pairs of
+// an element of `A` greater than one of `B` (problem 2-4).
+pub(crate) fn merge<T, F>(
+    A: &[T],
+    B: &[T],
+    out: &mut [T],
+    is_less: &mut F,
+) -> u64
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    debug_assert_eq!(out.len(), A.len() + B.len());
 
-fn merge<T: PartialOrd + Clone>(A: &[T], B: &[T], out: &mut Vec<T>) {
     let mut i = 0;
     let mut j = 0;
+    let mut inversions = 0;
 
-    for _ in 0..A.len() + B.len() {
-        if j == B.len() || i < A.len() && A[i] < B[j] {
-            out.push(A[i].clone());
+    // Ties are taken from `A` so that the sort stays stable.
+    for x in out.iter_mut() {
+        if j == B.len() || i < A.len() && !is_less(&B[j], &A[i]) {
+            *x = A[i].clone();
             i += 1;
         } else {
-            out.push(B[j].clone());
+            // `B[j]` is less than all of `A[i..]`.
+            inversions += (A.len() - i) as u64;
+            *x = B[j].clone();
             j += 1;
         }
     }
+    inversions
+}
+
+// SymMerge (Kim & Kutzner, 2004): merges the sorted runs `A[..m]` and
+// `A[m..]` in place and stably, using rotations instead of a buffer, in
+// O(n log n) time and O(log n) stack.
+fn sym_merge<T, F>(A: &mut [T], m: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let n = A.len();
+    if m == 0 || m == n {
+        return;
+    }
+
+    if m == 1 {
+        // Insert `A[0]` in front of the first element not less than it.
+        let (mut i, mut j) = (m, n);
+        while i < j {
+            let h = (i + j) / 2;
+            if is_less(&A[h], &A[0]) {
+                i = h + 1;
+            } else {
+                j = h;
+            }
+        }
+        A[..i].rotate_left(1);
+        return;
+    }
+
+    if n - m == 1 {
+        // Insert `A[m]` in front of the first element greater than it.
+        let (mut i, mut j) = (0, m);
+        while i < j {
+            let h = (i + j) / 2;
+            if !is_less(&A[m], &A[h]) {
+                i = h + 1;
+            } else {
+                j = h;
+            }
+        }
+        A[i..].rotate_right(1);
+        return;
+    }
+
+    // Binary search for `start`, such that after rotating `A[m..end]` in
+    // front of `A[start..m]` (`end` lies symmetric to `start` around
+    // `mid + m`), no element of `A[..mid]` is greater than one of
+    // `A[mid..]`.  Both halves are then merged recursively.
+    let mid = n / 2;
+    let k = mid + m;
+    let (mut start, mut r) = if m > mid {
+        (k - n, mid)
+    } else {
+        (0, m)
+    };
+    let p = k - 1;
+    while start < r {
+        let c = (start + r) / 2;
+        if !is_less(&A[p - c], &A[c]) {
+            start = c + 1;
+        } else {
+            r = c;
+        }
+    }
+    let end = k - start;
+
+    if start < m && m < end {
+        A[start..end].rotate_left(m - start);
+    }
+    if 0 < start && start < mid {
+        sym_merge(&mut A[..mid], start, is_less);
+    }
+    if mid < end && end < n {
+        sym_merge(&mut A[mid..], end - mid, is_less);
+    }
+}
+
+// Sorts `src` into `dst`, both holding the same elements on entry.  The
+// recursive calls swap the roles of the two arrays, so every level merges
+// straight into its destination and nothing has to be copied back.
+// Returns the number of inversions in `dst`.
+pub(crate) fn split_merge<T, F>(
+    src: &mut [T],
+    dst: &mut [T],
+    is_less: &mut F,
+) -> u64
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    if dst.len() <= 1 {
+        return 0;
+    }
+
+    let mid = dst.len() / 2;
+    split_merge(&mut dst[..mid], &mut src[..mid], is_less)
+        + split_merge(&mut dst[mid..], &mut src[mid..], is_less)
+        + merge(&src[..mid], &src[mid..], dst, is_less)
+}
+
+pub(crate) fn merge_sort_with_buffer_impl<T, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    is_less: &mut F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    buffer.clear();
+    buffer.extend_from_slice(A);
+    split_merge(buffer, A, is_less);
+}
+
+pub(crate) fn count_inversions_impl<T, F>(A: &[T], is_less: &mut F) -> u64
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut sorted = A.to_vec();
+    let mut buffer = A.to_vec();
+    split_merge(&mut buffer, &mut sorted, is_less)
+}
+
+fn merge_pass<T, F>(src: &[T], dst: &mut [T], width: usize, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let n = src.len();
+    for start in (0..n).step_by(2 * width) {
+        let mid = n.min(start + width);
+        let end = n.min(start + 2 * width);
+        merge(&src[start..mid], &src[mid..end], &mut dst[start..end], is_less);
+    }
+}
+
+pub(crate) fn merge_sort_bottom_up_impl<T, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    is_less: &mut F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    buffer.clear();
+    buffer.extend_from_slice(A);
+
+    let mut width = 1;
+    let mut sorted_in_A = true;
+    while width < A.len() {
+        if sorted_in_A {
+            merge_pass(A, buffer, width, is_less);
+        } else {
+            merge_pass(buffer, A, width, is_less);
+        }
+        sorted_in_A = !sorted_in_A;
+        width *= 2;
+    }
+
+    if !sorted_in_A {
+        A.clone_from_slice(buffer);
+    }
+}
+
+pub(crate) fn merge_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    merge_sort_with_buffer_impl(A, &mut Vec::new(), is_less);
 }
 
 pub fn merge_sort<T: PartialOrd + Clone>(A: &mut [T]) {
-    if A.len() <= 1 {
+    merge_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub(crate) fn in_place_merge_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= IN_PLACE_INSERTION_SORT_THRESHOLD {
+        insertion_sort_by_swap_impl(A, is_less);
         return;
     }
 
     let mid = A.len() / 2;
-    merge_sort(&mut A[..mid]);
-    merge_sort(&mut A[mid..]);
-    let mut temp = Vec::with_capacity(A.len());
-    merge(&A[..mid], &A[mid..], &mut temp);
-    A.clone_from_slice(&temp);
+    in_place_merge_sort_impl(&mut A[..mid], is_less);
+    in_place_merge_sort_impl(&mut A[mid..], is_less);
+    sym_merge(A, mid, is_less);
+}
+
+// `buffer` is only used as scratch space, and can be kept around to
+// avoid reallocating it for every call.
+pub fn merge_sort_with_buffer<T: PartialOrd + Clone>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+) {
+    merge_sort_with_buffer_impl(A, buffer, &mut compare::by_partial_ord());
+}
+
+pub fn merge_sort_with_buffer_by<T, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    compare: F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_with_buffer_impl(A, buffer, &mut compare::by_ordering(compare));
+}
+
+pub fn merge_sort_with_buffer_by_key<T, K, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    f: F,
+) where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_with_buffer_impl(A, buffer, &mut compare::by_key(f));
+}
+
+pub fn merge_sort_bottom_up<T: PartialOrd + Clone>(A: &mut [T]) {
+    merge_sort_bottom_up_impl(
+        A,
+        &mut Vec::new(),
+        &mut compare::by_partial_ord(),
+    );
+}
+
+pub fn merge_sort_bottom_up_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_bottom_up_impl(
+        A,
+        &mut Vec::new(),
+        &mut compare::by_ordering(compare),
+    );
+}
+
+pub fn merge_sort_bottom_up_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_bottom_up_impl(A, &mut Vec::new(), &mut compare::by_key(f));
+}
+
+pub fn merge_sort_bottom_up_with_buffer<T: PartialOrd + Clone>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+) {
+    merge_sort_bottom_up_impl(A, buffer, &mut compare::by_partial_ord());
+}
+
+pub fn merge_sort_bottom_up_with_buffer_by<T, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    compare: F,
+) where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_bottom_up_impl(A, buffer, &mut compare::by_ordering(compare));
+}
+
+pub fn merge_sort_bottom_up_with_buffer_by_key<T, K, F>(
+    A: &mut [T],
+    buffer: &mut Vec<T>,
+    f: F,
+) where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_bottom_up_impl(A, buffer, &mut compare::by_key(f));
+}
+
+pub fn in_place_merge_sort<T: PartialOrd>(A: &mut [T]) {
+    in_place_merge_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn in_place_merge_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    in_place_merge_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn in_place_merge_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    in_place_merge_sort_impl(A, &mut compare::by_key(f));
+}
+
+pub fn merge_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn merge_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    merge_sort_impl(A, &mut compare::by_key(f));
+}
+
+// Number of pairs `i < j` with `A[i] > A[j]` (problem 2-4), in O(n lg n)
+// time by merge sorting a copy of `A`.
+pub fn count_inversions<T: PartialOrd + Clone>(A: &[T]) -> u64 {
+    count_inversions_impl(A, &mut compare::by_partial_ord())
+}
+
+pub fn count_inversions_by<T, F>(A: &[T], compare: F) -> u64
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    count_inversions_impl(A, &mut compare::by_ordering(compare))
+}
+
+pub fn count_inversions_by_key<T, K, F>(A: &[T], f: F) -> u64
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    count_inversions_impl(A, &mut compare::by_key(f))
+}
+
+// Kendall tau distance between two rankings of the same items: the number of
+// pairs of items ranked in opposite orders by `A` and `B`, which is the
+// number of inversions in the positions in `A` of the items of `B`.
+pub fn kendall_tau_distance<T: Eq + Hash>(A: &[T], B: &[T]) -> u64 {
+    assert_eq!(A.len(), B.len(), "rankings must have the same length");
+
+    let rank: HashMap<&T, usize> =
+        A.iter().enumerate().map(|(i, x)| (x, i)).collect();
+    assert_eq!(rank.len(), A.len(), "ranking has repeated items");
+    let positions: Vec<usize> = B
+        .iter()
+        .map(|x| *rank.get(x).expect("rankings must hold the same items"))
+        .collect();
+    count_inversions(&positions)
 }
 
 mod tests {
@@ -37,4 +408,149 @@ mod tests {
         merge_sort(A.as_mut_slice());
         common::assert_asc(&A);
     }
+
+    #[test]
+    fn merge_sort_by() {
+        use super::merge_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(100);
+        merge_sort_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn merge_sort_by_key_stable() {
+        use super::merge_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(200, 10);
+        merge_sort_by_key(A.as_mut_slice(), |r| r.0);
+        common::assert_stable(&A);
+    }
+
+    #[test]
+    fn merge_sort_with_buffer() {
+        use super::{
+            merge_sort_with_buffer,
+            merge_sort_with_buffer_by_key,
+        };
+        use crate::common;
+        let mut buffer = Vec::new();
+        for &n in &[0, 1, 2, 7, 100, 1000] {
+            let mut A = common::random_vec::<f64>(n);
+            merge_sort_with_buffer(A.as_mut_slice(), &mut buffer);
+            assert_eq!(A.len(), n);
+            if n > 0 {
+                common::assert_asc(&A);
+            }
+        }
+        assert!(buffer.capacity() >= 1000);
+
+        let mut A = common::random_records(500, 10);
+        merge_sort_with_buffer_by_key(A.as_mut_slice(), &mut Vec::new(), |r| {
+            r.0
+        });
+        common::assert_stable(&A);
+    }
+
+    #[test]
+    fn merge_sort_bottom_up() {
+        use super::{
+            merge_sort_bottom_up,
+            merge_sort_bottom_up_by,
+            merge_sort_bottom_up_with_buffer_by_key,
+        };
+        use crate::common;
+        for &n in &[1, 2, 3, 64, 100, 1025] {
+            let mut A = common::random_vec::<i32>(n);
+            merge_sort_bottom_up(A.as_mut_slice());
+            common::assert_asc(&A);
+        }
+
+        let mut A = common::random_vec::<i32>(100);
+        merge_sort_bottom_up_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+
+        let mut buffer = Vec::new();
+        let mut A = common::random_records(1000, 10);
+        merge_sort_bottom_up_with_buffer_by_key(
+            A.as_mut_slice(),
+            &mut buffer,
+            |r| r.0,
+        );
+        common::assert_stable(&A);
+    }
+
+    #[test]
+    fn in_place_merge_sort() {
+        use super::in_place_merge_sort;
+        use crate::common;
+        for &n in &[1, 2, 13, 100, 1000, 10_000] {
+            let mut A = common::random_vec::<f64>(n);
+            in_place_merge_sort(A.as_mut_slice());
+            common::assert_asc(&A);
+        }
+    }
+
+    #[test]
+    fn in_place_merge_sort_by_key_stable() {
+        use super::in_place_merge_sort_by_key;
+        use crate::common;
+
+        // Not `Clone`, so the sort can only move elements around.
+        #[derive(Debug)]
+        struct Record(u32, usize);
+
+        for &n_keys in &[2, 10, 1000] {
+            let mut A: Vec<_> = common::random_records(5000, n_keys)
+                .into_iter()
+                .map(|(key, i)| Record(key, i))
+                .collect();
+            in_place_merge_sort_by_key(A.as_mut_slice(), |r| r.0);
+            let A: Vec<_> = A.into_iter().map(|r| (r.0, r.1)).collect();
+            common::assert_stable(&A);
+        }
+    }
+
+    #[test]
+    fn count_inversions() {
+        use super::count_inversions;
+        use crate::common;
+        assert_eq!(count_inversions(&[2, 3, 8, 6, 1]), 5);
+        assert_eq!(count_inversions::<i32>(&[]), 0);
+        assert_eq!(count_inversions(&[1, 1, 1]), 0);
+        for &n in &[1, 2, 3, 10, 100, 500] {
+            let A = common::random_vec_range::<i32>(n, 0, 20);
+            assert_eq!(
+                count_inversions(&A),
+                common::brute_force_inversions(&A)
+            );
+        }
+
+        let A: Vec<u32> = (0..1000).rev().collect();
+        assert_eq!(count_inversions(&A), 1000 * 999 / 2);
+    }
+
+    #[test]
+    fn count_inversions_by_key() {
+        use super::{
+            count_inversions_by,
+            count_inversions_by_key,
+        };
+        use crate::common;
+        let A = common::random_records(300, 30);
+        let keys: Vec<u32> = A.iter().map(|r| r.0).collect();
+        let expected = common::brute_force_inversions(&keys);
+        assert_eq!(count_inversions_by_key(&A, |r| r.0), expected);
+        assert_eq!(count_inversions_by(&A, |a, b| a.0.cmp(&b.0)), expected);
+    }
+
+    #[test]
+    fn kendall_tau_distance() {
+        use super::kendall_tau_distance;
+        let A = ["a", "b", "c", "d", "e"];
+        assert_eq!(kendall_tau_distance(&A, &A), 0);
+        assert_eq!(kendall_tau_distance(&A, &["e", "d", "c", "b", "a"]), 10);
+        assert_eq!(kendall_tau_distance(&A, &["c", "d", "a", "b", "e"]), 4);
+        assert_eq!(kendall_tau_distance(&["c", "d", "a", "b", "e"], &A), 4);
+    }
 }
@@ -1,3 +1,6 @@
+pub mod adaptive_merge_sort;
 pub mod bubble_sort;
+pub mod external_merge_sort;
 pub mod insertion_sort;
 pub mod merge_sort;
+pub mod search;
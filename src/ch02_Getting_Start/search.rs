@@ -0,0 +1,352 @@
+use crate::{
+    ch02_Getting_Start::merge_sort::merge_sort,
+    common::compare,
+};
+use num::CheckedSub;
+use std::{
+    cmp::{
+        Ordering,
+        PartialOrd,
+    },
+    ops::Range,
+};
+
+// Every search here looks for a partition point: `A` must be partitioned by
+// `pred`, all the elements satisfying it coming first, and the result is the
+// number of those elements.
+
+pub(crate) fn partition_point<T, P>(A: &[T], mut pred: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let (mut low, mut high) = (0, A.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if pred(&A[mid]) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+// Probes positions 1, 3, 7, ... before the binary search, so the cost is
+// O(lg k) for an answer `k`.
+pub(crate) fn gallop<T, P>(A: &[T], mut pred: P) -> usize
+where
+    P: FnMut(&T) -> bool,
+{
+    let mut low = 0;
+    let mut high = 1;
+    while high <= A.len() && pred(&A[high - 1]) {
+        low = high;
+        high = 2 * high + 1;
+    }
+
+    let high = high.min(A.len() + 1) - 1;
+    low + partition_point(&A[low..high], pred)
+}
+
+// The index of the first element of `A[i..]` not before the target, if it is
+// the target itself.
+fn found<T, F>(A: &[T], i: usize, mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    if i < A.len() && f(&A[i]) == Ordering::Equal {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+// The first index of an element not less than `x`.
+pub fn lower_bound<T: PartialOrd>(A: &[T], x: &T) -> usize {
+    partition_point(A, |y| y < x)
+}
+
+// `f` compares an element with the target.
+pub fn lower_bound_by<T, F>(A: &[T], mut f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    partition_point(A, |y| f(y) == Ordering::Less)
+}
+
+pub fn lower_bound_by_key<T, K, F>(A: &[T], key: &K, mut f: F) -> usize
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    partition_point(A, |y| f(y) < *key)
+}
+
+// The first index of an element greater than `x`.
+pub fn upper_bound<T: PartialOrd>(A: &[T], x: &T) -> usize {
+    let mut is_less = compare::by_partial_ord();
+    partition_point(A, |y| !is_less(x, y))
+}
+
+pub fn upper_bound_by<T, F>(A: &[T], mut f: F) -> usize
+where
+    F: FnMut(&T) -> Ordering,
+{
+    partition_point(A, |y| f(y) != Ordering::Greater)
+}
+
+pub fn upper_bound_by_key<T, K, F>(A: &[T], key: &K, mut f: F) -> usize
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    let mut is_less = compare::by_partial_ord();
+    partition_point(A, |y| !is_less(key, &f(y)))
+}
+
+// The indices of the elements equal to `x`.
+pub fn equal_range<T: PartialOrd>(A: &[T], x: &T) -> Range<usize> {
+    lower_bound(A, x)..upper_bound(A, x)
+}
+
+pub fn equal_range_by<T, F>(A: &[T], mut f: F) -> Range<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    lower_bound_by(A, &mut f)..upper_bound_by(A, &mut f)
+}
+
+pub fn equal_range_by_key<T, K, F>(A: &[T], key: &K, mut f: F) -> Range<usize>
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    lower_bound_by_key(A, key, &mut f)..upper_bound_by_key(A, key, &mut f)
+}
+
+// BINARY-SEARCH (exercise 2.3-5): the index of the first element equal to
+// `x`, in O(lg n) time.
+pub fn binary_search<T: PartialOrd>(A: &[T], x: &T) -> Option<usize> {
+    binary_search_by(A, |y| y.partial_cmp(x).unwrap_or(Ordering::Less))
+}
+
+pub fn binary_search_by<T, F>(A: &[T], mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let i = lower_bound_by(A, &mut f);
+    found(A, i, f)
+}
+
+pub fn binary_search_by_key<T, K, F>(
+    A: &[T],
+    key: &K,
+    mut f: F,
+) -> Option<usize>
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    binary_search_by(A, |y| f(y).partial_cmp(key).unwrap_or(Ordering::Less))
+}
+
+// Like `binary_search`, but in O(lg i) time for an answer `i`, which pays
+// off when the target is near the front of a long or unbounded sequence.
+pub fn exponential_search<T: PartialOrd>(A: &[T], x: &T) -> Option<usize> {
+    exponential_search_by(A, |y| y.partial_cmp(x).unwrap_or(Ordering::Less))
+}
+
+pub fn exponential_search_by<T, F>(A: &[T], mut f: F) -> Option<usize>
+where
+    F: FnMut(&T) -> Ordering,
+{
+    let i = gallop(A, |y| f(y) == Ordering::Less);
+    found(A, i, f)
+}
+
+pub fn exponential_search_by_key<T, K, F>(
+    A: &[T],
+    key: &K,
+    mut f: F,
+) -> Option<usize>
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    exponential_search_by(A, |y| {
+        f(y).partial_cmp(key).unwrap_or(Ordering::Less)
+    })
+}
+
+// Interpolation search guesses where `key` lies from the keys at both ends
+// of the range, taking O(lg lg n) probes on average over uniformly
+// distributed keys but O(n) in the worst case.  It needs numeric keys, so
+// there is no comparator flavour.  Returns the index of some element with
+// the given key.
+pub fn interpolation_search_by_key<T, F>(
+    A: &[T],
+    key: f64,
+    mut f: F,
+) -> Option<usize>
+where
+    F: FnMut(&T) -> f64,
+{
+    if A.is_empty() {
+        return None;
+    }
+
+    let (mut low, mut high) = (0, A.len() - 1);
+    let (mut k_low, mut k_high) = (f(&A[low]), f(&A[high]));
+    while low <= high && k_low <= key && key <= k_high {
+        if k_low == k_high {
+            return Some(low);
+        }
+
+        let t = (key - k_low) / (k_high - k_low);
+        let mid = low + ((t * (high - low) as f64) as usize).min(high - low);
+        let k_mid = f(&A[mid]);
+        if k_mid < key {
+            low = mid + 1;
+            if low > high {
+                break;
+            }
+            k_low = f(&A[low]);
+        } else if key < k_mid {
+            if mid == 0 {
+                break;
+            }
+            high = mid - 1;
+            k_high = f(&A[high]);
+        } else {
+            return Some(mid);
+        }
+    }
+    None
+}
+
+pub fn interpolation_search<T>(A: &[T], x: T) -> Option<usize>
+where
+    T: Copy + Into<f64>,
+{
+    interpolation_search_by_key(A, x.into(), |&y| y.into())
+}
+
+// Exercise 2.3-7: two elements of `S` whose sum is exactly `x`, found in
+// O(n lg n) time by sorting a copy of `S` and binary searching for the
+// complement of every element among the elements after it.
+pub fn pair_with_sum<T>(S: &[T], x: T) -> Option<(T, T)>
+where
+    T: PartialOrd + Copy + CheckedSub,
+{
+    let mut A = S.to_vec();
+    merge_sort(&mut A);
+
+    for (i, a) in A.iter().enumerate() {
+        if let Some(b) = x.checked_sub(a) {
+            if binary_search(&A[i + 1..], &b).is_some() {
+                return Some((*a, b));
+            }
+        }
+    }
+    None
+}
+
+mod tests {
+    #[test]
+    fn bounds() {
+        use super::{
+            equal_range,
+            lower_bound,
+            upper_bound,
+        };
+        use crate::common;
+        let mut A = common::random_vec_range::<i32>(300, 0, 50);
+        A.sort();
+        for x in -1..52 {
+            let lower = A.iter().filter(|&&y| y < x).count();
+            let upper = A.iter().filter(|&&y| y <= x).count();
+            assert_eq!(lower_bound(&A, &x), lower);
+            assert_eq!(upper_bound(&A, &x), upper);
+            assert_eq!(equal_range(&A, &x), lower..upper);
+        }
+        assert_eq!(lower_bound(&[], &1), 0);
+        assert_eq!(equal_range(&[], &1), 0..0);
+    }
+
+    #[test]
+    fn bounds_by_key() {
+        use super::{
+            equal_range_by,
+            equal_range_by_key,
+            lower_bound_by_key,
+            upper_bound_by,
+        };
+        let A = [(1, 'a'), (3, 'b'), (3, 'c'), (3, 'd'), (8, 'e')];
+        assert_eq!(lower_bound_by_key(&A, &3, |r| r.0), 1);
+        assert_eq!(upper_bound_by(&A, |r| r.0.cmp(&3)), 4);
+        assert_eq!(equal_range_by_key(&A, &3, |r| r.0), 1..4);
+        assert_eq!(equal_range_by(&A, |r| r.0.cmp(&5)), 4..4);
+        assert_eq!(equal_range_by_key(&A, &9, |r| r.0), 5..5);
+    }
+
+    #[test]
+    fn searches() {
+        use super::{
+            binary_search,
+            exponential_search,
+            interpolation_search,
+        };
+        use crate::common;
+        for &n in &[0, 1, 2, 10, 1000] {
+            let mut A = common::random_vec_range::<i32>(n, -100, 100);
+            A.sort();
+            for x in -102..102 {
+                let first = A.iter().position(|&y| y == x);
+                assert_eq!(binary_search(&A, &x), first);
+                assert_eq!(exponential_search(&A, &x), first);
+                match interpolation_search(&A, x) {
+                    Some(i) => assert_eq!(A[i], x),
+                    None => assert_eq!(first, None),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn searches_by_key() {
+        use super::{
+            binary_search_by,
+            binary_search_by_key,
+            exponential_search_by_key,
+            interpolation_search_by_key,
+        };
+        let A = [(0.5, 'a'), (1.5, 'b'), (1.5, 'c'), (4.0, 'd'), (9.0, 'e')];
+        assert_eq!(binary_search_by_key(&A, &1.5, |r| r.0), Some(1));
+        assert_eq!(binary_search_by(&A, |r| r.1.cmp(&'d')), Some(3));
+        assert_eq!(exponential_search_by_key(&A, &9.0, |r| r.0), Some(4));
+        assert_eq!(exponential_search_by_key(&A, &2.0, |r| r.0), None);
+        assert_eq!(interpolation_search_by_key(&A, 4.0, |r| r.0), Some(3));
+        assert_eq!(interpolation_search_by_key(&A, 10.0, |r| r.0), None);
+    }
+
+    #[test]
+    fn pair_with_sum() {
+        use super::pair_with_sum;
+        use crate::common;
+        assert_eq!(pair_with_sum(&[8, 1, 5, 3], 9), Some((1, 8)));
+        assert_eq!(pair_with_sum(&[4, 1, 5], 8), None);
+        assert_eq!(pair_with_sum(&[4, 1, 4], 8), Some((4, 4)));
+        assert_eq!(pair_with_sum::<u8>(&[], 0), None);
+        assert_eq!(pair_with_sum(&[200u8, 3], 2), None);
+
+        let S = common::random_vec_range::<i32>(200, -1000, 1000);
+        for x in -50..50 {
+            let brute = (0..S.len())
+                .any(|i| (i + 1..S.len()).any(|j| S[i] + S[j] == x));
+            match pair_with_sum(&S, x) {
+                Some((a, b)) => assert!(brute && a + b == x),
+                None => assert!(!brute),
+            }
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use super::{
+    dary_child,
+    dary_parent,
+};
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+// MAX-HEAPIFY for a `d`-ary heap (CLRS problem 6-2).
+fn dary_heapify<T, F>(A: &mut [T], mut i: usize, d: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let mut largest = i;
+        let first = dary_child(i, 0, d);
+        for c in first..A.len().min(first + d) {
+            if is_less(&A[largest], &A[c]) {
+                largest = c;
+            }
+        }
+        if largest == i {
+            return;
+        }
+        A.swap(i, largest);
+        i = largest;
+    }
+}
+
+fn dary_init_heap<T, F>(A: &mut [T], d: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
+    let last_parent = dary_parent(A.len() - 1, d);
+    for i in (0..last_parent + 1).rev() {
+        dary_heapify(A, i, d, is_less);
+    }
+}
+
+fn dary_sift_up<T, F>(A: &mut [T], mut i: usize, d: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    while i > 0 && is_less(&A[dary_parent(i, d)], &A[i]) {
+        A.swap(i, dary_parent(i, d));
+        i = dary_parent(i, d);
+    }
+}
+
+pub(crate) fn dary_heap_sort_impl<T, F>(A: &mut [T], d: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    assert!(d >= 2);
+    if A.len() <= 1 {
+        return;
+    }
+
+    dary_init_heap(A, d, is_less);
+
+    for i in (1..A.len()).rev() {
+        A.swap(0, i);
+        dary_heapify(&mut A[..i], 0, d, is_less);
+    }
+}
+
+pub fn dary_heap_sort<T: PartialOrd>(A: &mut [T], d: usize) {
+    dary_heap_sort_impl(A, d, &mut compare::by_partial_ord());
+}
+
+pub fn dary_heap_sort_by<T, F>(A: &mut [T], d: usize, compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    dary_heap_sort_impl(A, d, &mut compare::by_ordering(compare));
+}
+
+pub fn dary_heap_sort_by_key<T, K, F>(A: &mut [T], d: usize, f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    dary_heap_sort_impl(A, d, &mut compare::by_key(f));
+}
+
+fn less<T: PartialOrd>(a: &T, b: &T) -> bool {
+    a < b
+}
+
+// A max-priority queue on a `d`-ary heap.  Higher arities make the heap
+// shallower, trading cheaper `insert` and `increase_key` for a more
+// expensive `extract_max`.
+pub struct DaryHeap<T: PartialOrd> {
+    heap: Vec<T>,
+    d: usize,
+}
+
+impl<T: PartialOrd> DaryHeap<T> {
+    pub fn new(d: usize) -> DaryHeap<T> {
+        DaryHeap::from_vec(Vec::new(), d)
+    }
+
+    // Builds the heap in O(n) time.
+    pub fn from_vec(mut heap: Vec<T>, d: usize) -> DaryHeap<T> {
+        assert!(d >= 2);
+        dary_init_heap(&mut heap, d, &mut less);
+        DaryHeap {
+            heap,
+            d,
+        }
+    }
+
+    pub fn arity(&self) -> usize {
+        self.d
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn maximum(&self) -> Option<&T> {
+        self.heap.first()
+    }
+
+    pub fn extract_max(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let max = self.heap.swap_remove(0);
+        dary_heapify(&mut self.heap, 0, self.d, &mut less);
+        Some(max)
+    }
+
+    pub fn insert(&mut self, key: T) {
+        self.heap.push(key);
+        let i = self.heap.len() - 1;
+        dary_sift_up(&mut self.heap, i, self.d, &mut less);
+    }
+
+    // Raises the key at heap position `i` to `key`, which must not be
+    // smaller than the current one.
+    pub fn increase_key(&mut self, i: usize, key: T) {
+        assert!(self.heap[i] <= key, "new key is smaller than current key");
+        self.heap[i] = key;
+        dary_sift_up(&mut self.heap, i, self.d, &mut less);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.heap
+    }
+
+    // The elements in ascending order.
+    pub fn into_sorted_vec(mut self) -> Vec<T> {
+        for i in (1..self.heap.len()).rev() {
+            self.heap.swap(0, i);
+            dary_heapify(&mut self.heap[..i], 0, self.d, &mut less);
+        }
+        self.heap
+    }
+}
+
+mod tests {
+    #[test]
+    fn dary_heap_sort() {
+        use super::dary_heap_sort;
+        use crate::common;
+        for &d in &[2, 3, 4, 8] {
+            for &n in &[0, 1, 2, 9, 100, 1000] {
+                let mut A = common::random_vec::<f64>(n);
+                dary_heap_sort(A.as_mut_slice(), d);
+                if n > 0 {
+                    common::assert_asc(&A);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dary_heap_sort_by() {
+        use super::dary_heap_sort_by;
+        use crate::common;
+        for &d in &[2, 3, 4, 8] {
+            let mut A = common::random_vec::<i32>(1000);
+            dary_heap_sort_by(A.as_mut_slice(), d, |a, b| b.cmp(a));
+            common::assert_desc(&A);
+        }
+    }
+
+    #[test]
+    fn dary_heap() {
+        use super::DaryHeap;
+        use crate::common;
+        for &d in &[2, 3, 4, 8] {
+            let A = common::random_vec_range::<i32>(500, -1000, 1000);
+            let mut heap = DaryHeap::from_vec(A[..250].to_vec(), d);
+            for &x in A[250..].iter() {
+                heap.insert(x);
+            }
+            assert_eq!(heap.len(), 500);
+
+            heap.increase_key(heap.len() - 1, 5000);
+            assert_eq!(heap.maximum(), Some(&5000));
+
+            let mut extracted = Vec::new();
+            while let Some(x) = heap.extract_max() {
+                extracted.push(x);
+            }
+            assert_eq!(extracted.len(), 500);
+            common::assert_desc(&extracted);
+
+            let heap = DaryHeap::from_vec(A, d);
+            common::assert_asc(&heap.into_sorted_vec());
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use super::{
+    left,
+    parent,
+    right,
+};
+use std::cmp::PartialOrd;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+
+// Refers to an element of an `IndexedMinHeap` for as long as it stays in
+// the heap.  A handle names the heap that issued it and a slot of that heap
+// along with the generation of the slot, so handles of other heaps and
+// handles whose element is gone are rejected even once the slot is reused.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Handle {
+    heap: usize,
+    slot: usize,
+    generation: u64,
+}
+
+struct Slot {
+    generation: u64,
+    // Heap position of the element, `None` while the slot is free.
+    position: Option<usize>,
+}
+
+// A min-heap that also tracks where every element sits, so that keys can
+// be changed and elements deleted through their handle in O(log n).
+pub struct IndexedMinHeap<T: PartialOrd> {
+    id: usize,
+    heap: Vec<(T, Handle)>,
+    slots: Vec<Slot>,
+    // Slots left by removed elements, reused before growing `slots`.
+    free: Vec<usize>,
+}
+
+impl<T: PartialOrd> IndexedMinHeap<T> {
+    pub fn new() -> IndexedMinHeap<T> {
+        static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+        IndexedMinHeap {
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            heap: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    // `None` for handles removed from this heap or issued by another one.
+    fn position(&self, handle: Handle) -> Option<usize> {
+        if handle.heap != self.id {
+            return None;
+        }
+        self.slots
+            .get(handle.slot)
+            .filter(|slot| slot.generation == handle.generation)
+            .and_then(|slot| slot.position)
+    }
+
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|i| &self.heap[i].0)
+    }
+
+    pub fn minimum(&self) -> Option<(Handle, &T)> {
+        self.heap.first().map(|(key, handle)| (*handle, key))
+    }
+
+    pub fn insert(&mut self, key: T) -> Handle {
+        let slot = match self.free.pop() {
+            Some(slot) => slot,
+            None => {
+                self.slots.push(Slot {
+                    generation: 0,
+                    position: None,
+                });
+                self.slots.len() - 1
+            }
+        };
+        self.slots[slot].position = Some(self.heap.len());
+        let handle = Handle {
+            heap: self.id,
+            slot,
+            generation: self.slots[slot].generation,
+        };
+        self.heap.push((key, handle));
+        self.sift_up(self.heap.len() - 1);
+        handle
+    }
+
+    pub fn extract_min(&mut self) -> Option<(Handle, T)> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let (key, handle) = self.remove_at(0);
+        Some((handle, key))
+    }
+
+    pub fn delete(&mut self, handle: Handle) -> Option<T> {
+        let i = self.position(handle)?;
+        Some(self.remove_at(i).0)
+    }
+
+    pub fn decrease_key(&mut self, handle: Handle, key: T) {
+        let i = self.position(handle).expect("handle is not in the heap");
+        assert!(key <= self.heap[i].0, "new key is greater than current key");
+        self.heap[i].0 = key;
+        self.sift_up(i);
+    }
+
+    pub fn increase_key(&mut self, handle: Handle, key: T) {
+        let i = self.position(handle).expect("handle is not in the heap");
+        assert!(self.heap[i].0 <= key, "new key is smaller than current key");
+        self.heap[i].0 = key;
+        self.sift_down(i);
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.heap.swap(i, j);
+        self.slots[self.heap[i].1.slot].position = Some(i);
+        self.slots[self.heap[j].1.slot].position = Some(j);
+    }
+
+    fn remove_at(&mut self, i: usize) -> (T, Handle) {
+        let last = self.heap.len() - 1;
+        self.swap(i, last);
+        let (key, handle) = self.heap.pop().unwrap();
+        let slot = &mut self.slots[handle.slot];
+        slot.position = None;
+        slot.generation += 1;
+        self.free.push(handle.slot);
+
+        // The element moved into `i` may belong either above or below.
+        if i < self.heap.len() {
+            self.sift_up(i);
+            self.sift_down(i);
+        }
+        (key, handle)
+    }
+
+    fn sift_up(&mut self, mut i: usize) {
+        while i > 0 && self.heap[i].0 < self.heap[parent(i)].0 {
+            self.swap(i, parent(i));
+            i = parent(i);
+        }
+    }
+
+    fn sift_down(&mut self, mut i: usize) {
+        loop {
+            let (l, r) = (left(i), right(i));
+            let mut smallest = i;
+            if l < self.heap.len() && self.heap[l].0 < self.heap[smallest].0 {
+                smallest = l;
+            }
+            if r < self.heap.len() && self.heap[r].0 < self.heap[smallest].0 {
+                smallest = r;
+            }
+            if smallest == i {
+                return;
+            }
+            self.swap(i, smallest);
+            i = smallest;
+        }
+    }
+}
+
+impl<T: PartialOrd> Default for IndexedMinHeap<T> {
+    fn default() -> IndexedMinHeap<T> {
+        IndexedMinHeap::new()
+    }
+}
+
+mod tests {
+    #[test]
+    fn indexed_min_heap() {
+        use super::IndexedMinHeap;
+        let mut heap = IndexedMinHeap::new();
+        let a = heap.insert(5);
+        let b = heap.insert(3);
+        let c = heap.insert(8);
+        assert_eq!(heap.minimum(), Some((b, &3)));
+
+        heap.decrease_key(c, 1);
+        assert_eq!(heap.minimum(), Some((c, &1)));
+        heap.increase_key(c, 9);
+        assert_eq!(heap.delete(b), Some(3));
+        assert!(!heap.contains(b));
+        assert_eq!(heap.delete(b), None);
+
+        assert_eq!(heap.extract_min(), Some((a, 5)));
+        assert_eq!(heap.extract_min(), Some((c, 9)));
+        assert_eq!(heap.extract_min(), None);
+    }
+
+    #[test]
+    fn indexed_min_heap_foreign_handle() {
+        use super::IndexedMinHeap;
+        let mut other = IndexedMinHeap::new();
+        let first = other.insert(1);
+        let second = other.insert(2);
+
+        // `first` sits in the same slot as `own`.
+        let mut heap = IndexedMinHeap::new();
+        let own = heap.insert(5);
+        for &foreign in &[first, second] {
+            assert!(!heap.contains(foreign));
+            assert_eq!(heap.get(foreign), None);
+            assert_eq!(heap.delete(foreign), None);
+        }
+        assert_eq!(heap.len(), 1);
+        assert_eq!(heap.get(own), Some(&5));
+    }
+
+    #[test]
+    fn indexed_min_heap_stale_handle() {
+        use super::IndexedMinHeap;
+        let mut heap = IndexedMinHeap::new();
+        let old = heap.insert(5);
+        assert_eq!(heap.delete(old), Some(5));
+
+        // The new element reuses the slot of the old one.
+        let new = heap.insert(8);
+        assert!(!heap.contains(old));
+        assert_eq!(heap.delete(old), None);
+        assert_eq!(heap.get(new), Some(&8));
+
+        for i in 0..1000 {
+            let handle = heap.insert(i);
+            heap.delete(handle);
+        }
+        assert_eq!(heap.slots.len(), 2);
+    }
+
+    #[test]
+    fn indexed_min_heap_against_oracle() {
+        use super::IndexedMinHeap;
+        use rand::Rng;
+
+        let mut rng = rand::thread_rng();
+        let mut heap = IndexedMinHeap::new();
+        // Sorted by key, then by handle insertion order.
+        let mut oracle: Vec<(i32, usize)> = Vec::new();
+        let mut handles = Vec::new();
+
+        for _ in 0..10_000 {
+            match rng.gen_range(0, 5) {
+                0 | 1 => {
+                    let key = rng.gen_range(-1000, 1000);
+                    handles.push(heap.insert(key));
+                    oracle.push((key, handles.len() - 1));
+                }
+                2 => {
+                    if let Some((handle, key)) = heap.extract_min() {
+                        assert_eq!(key, oracle[0].0);
+                        let h =
+                            handles.iter().position(|&x| x == handle).unwrap();
+                        let k =
+                            oracle.iter().position(|&(_, x)| x == h).unwrap();
+                        assert_eq!(oracle.remove(k).0, key);
+                    } else {
+                        assert!(oracle.is_empty());
+                    }
+                }
+                op => {
+                    if oracle.is_empty() {
+                        continue;
+                    }
+                    let k = rng.gen_range(0, oracle.len());
+                    let (key, h) = oracle[k];
+                    let handle = handles[h];
+                    assert!(heap.contains(handle));
+                    assert_eq!(heap.get(handle), Some(&key));
+                    if op == 3 {
+                        let new_key = key - rng.gen_range(0, 500);
+                        heap.decrease_key(handle, new_key);
+                        oracle[k].0 = new_key;
+                    } else if rng.gen() {
+                        let new_key = key + rng.gen_range(0, 500);
+                        heap.increase_key(handle, new_key);
+                        oracle[k].0 = new_key;
+                    } else {
+                        assert_eq!(heap.delete(handle), Some(key));
+                        oracle.remove(k);
+                    }
+                }
+            }
+            oracle.sort();
+            assert_eq!(heap.len(), oracle.len());
+            assert_eq!(
+                heap.minimum().map(|(_, &key)| key),
+                oracle.first().map(|x| x.0)
+            );
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use super::{
+    heapify,
+    init_heap,
+};
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+// The heap primitives build max-heaps, so "less" here means "to be yielded
+// later".
+fn later<'a, T, F>(
+    is_less: &'a mut F,
+) -> impl FnMut(&(T, usize), &(T, usize)) -> bool + 'a
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    move |a, b| is_less(&b.0, &a.0) || !is_less(&a.0, &b.0) && b.1 < a.1
+}
+
+// Merges k sorted sequences in O(n lg k) time (exercise 6.5-9).  The heap
+// holds the head of every sequence that is not yet exhausted, together with
+// the index of that sequence, which breaks ties in favour of the earlier one.
+pub struct KWayMerge<I: Iterator, F> {
+    sources: Vec<I>,
+    heap: Vec<(I::Item, usize)>,
+    is_less: F,
+}
+
+impl<I, F> KWayMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> bool,
+{
+    fn new(sources: Vec<I>, is_less: F) -> KWayMerge<I, F> {
+        let mut merge = KWayMerge {
+            heap: Vec::with_capacity(sources.len()),
+            sources,
+            is_less,
+        };
+        for (i, source) in merge.sources.iter_mut().enumerate() {
+            if let Some(head) = source.next() {
+                merge.heap.push((head, i));
+            }
+        }
+        init_heap(&mut merge.heap, &mut later(&mut merge.is_less));
+        merge
+    }
+}
+
+impl<I, F> Iterator for KWayMerge<I, F>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> bool,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let i = self.heap[0].1;
+        let top = match self.sources[i].next() {
+            Some(head) => std::mem::replace(&mut self.heap[0], (head, i)),
+            None => self.heap.swap_remove(0),
+        };
+
+        if !self.heap.is_empty() {
+            heapify(&mut self.heap, 0, &mut later(&mut self.is_less));
+        }
+        Some(top.0)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut low = self.heap.len();
+        let mut high = Some(self.heap.len());
+        for source in &self.sources {
+            let (l, h) = source.size_hint();
+            low = low.saturating_add(l);
+            high = match (high, h) {
+                (Some(a), Some(b)) => a.checked_add(b),
+                _ => None,
+            };
+        }
+        (low, high)
+    }
+}
+
+pub fn kway_merge<S, T>(
+    sources: S,
+) -> KWayMerge<<S::Item as IntoIterator>::IntoIter, impl FnMut(&T, &T) -> bool>
+where
+    S: IntoIterator,
+    S::Item: IntoIterator<Item = T>,
+    T: PartialOrd,
+{
+    KWayMerge::new(
+        sources.into_iter().map(IntoIterator::into_iter).collect(),
+        compare::by_partial_ord(),
+    )
+}
+
+pub fn kway_merge_by<S, T, F>(
+    sources: S,
+    compare: F,
+) -> KWayMerge<<S::Item as IntoIterator>::IntoIter, impl FnMut(&T, &T) -> bool>
+where
+    S: IntoIterator,
+    S::Item: IntoIterator<Item = T>,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    KWayMerge::new(
+        sources.into_iter().map(IntoIterator::into_iter).collect(),
+        compare::by_ordering(compare),
+    )
+}
+
+pub fn kway_merge_by_key<S, T, K, F>(
+    sources: S,
+    f: F,
+) -> KWayMerge<<S::Item as IntoIterator>::IntoIter, impl FnMut(&T, &T) -> bool>
+where
+    S: IntoIterator,
+    S::Item: IntoIterator<Item = T>,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    KWayMerge::new(
+        sources.into_iter().map(IntoIterator::into_iter).collect(),
+        compare::by_key(f),
+    )
+}
+
+pub fn merge_sorted_slices<T: PartialOrd + Clone>(slices: &[&[T]]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(slices.iter().map(|s| s.len()).sum());
+    merged.extend(kway_merge(slices.iter().map(|s| s.iter().cloned())));
+    merged
+}
+
+mod tests {
+    #[test]
+    fn kway_merge() {
+        use super::kway_merge;
+        use crate::common;
+        for &k in &[0, 1, 2, 3, 8, 50] {
+            let mut sources = Vec::new();
+            let mut all = Vec::new();
+            for i in 0..k {
+                let mut source =
+                    common::random_vec_range::<i32>(i * 7 % 40, -50, 50);
+                source.sort();
+                all.extend_from_slice(&source);
+                sources.push(source);
+            }
+            all.sort();
+
+            let merged = kway_merge(sources);
+            assert_eq!(merged.size_hint(), (all.len(), Some(all.len())));
+            assert_eq!(merged.collect::<Vec<_>>(), all);
+        }
+    }
+
+    #[test]
+    fn kway_merge_stable() {
+        use super::kway_merge_by_key;
+        use crate::ch02_Getting_Start::merge_sort::merge_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(1000, 10);
+        for chunk in A.chunks_mut(137) {
+            merge_sort_by_key(chunk, |r| r.0);
+        }
+
+        let sources = A.chunks(137).map(|chunk| chunk.iter().cloned());
+        let merged: Vec<_> = kway_merge_by_key(sources, |r| r.0).collect();
+        assert_eq!(merged.len(), 1000);
+        common::assert_stable(&merged);
+    }
+
+    #[test]
+    fn merge_sorted_slices() {
+        use super::merge_sorted_slices;
+        let A = [1, 4, 7, 10];
+        let B = [];
+        let C = [2, 4, 4, 11, 12];
+        let D = [0];
+        assert_eq!(
+            merge_sorted_slices(&[&A, &B, &C, &D]),
+            vec![0, 1, 2, 4, 4, 4, 7, 10, 11, 12]
+        );
+        assert!(merge_sorted_slices::<i32>(&[]).is_empty());
+    }
+}
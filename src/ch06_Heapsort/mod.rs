@@ -1,56 +1,193 @@
-use std::cmp::PartialOrd;
+pub mod dary_heap;
+pub mod indexed_heap;
+pub mod kway_merge;
+pub mod priority_queue;
+pub mod young_tableau;
+
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+// Index helpers for a heap where every node has `d` children.
+#[inline]
+fn dary_parent(i: usize, d: usize) -> usize {
+    (i - 1) / d
+}
+
+// The `k`-th child of `i`, with `k` starting from 0.
+#[inline]
+fn dary_child(i: usize, k: usize, d: usize) -> usize {
+    d * i + k + 1
+}
 
 #[inline]
 fn parent(i: usize) -> usize {
-    (i + 1) / 2 - 1
+    dary_parent(i, 2)
 }
 
 #[inline]
 fn left(i: usize) -> usize {
-    (i + 1) * 2 - 1
+    dary_child(i, 0, 2)
 }
 
 #[inline]
 fn right(i: usize) -> usize {
-    (i + 1) * 2
+    dary_child(i, 1, 2)
 }
 
-fn init_heap<T: PartialOrd>(A: &mut [T]) {
+pub(crate) fn init_heap<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
+
     let last_parent = parent(A.len() - 1);
     for i in (0..last_parent + 1).rev() {
-        heapify(A, i);
+        heapify(A, i, is_less);
     }
 }
 
-fn heapify<T: PartialOrd>(A: &mut [T], i: usize) {
-    let (l, r) = (left(i), right(i));
-    let mut largest = if l < A.len() && A[l] > A[i] {
-        l
-    } else {
-        i
-    };
-    if r < A.len() && A[r] > A[largest] {
-        largest = r;
-    }
-    if largest != i {
+pub(crate) fn heapify<T, F>(A: &mut [T], mut i: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let (l, r) = (left(i), right(i));
+        let mut largest = if l < A.len() && is_less(&A[i], &A[l]) {
+            l
+        } else {
+            i
+        };
+        if r < A.len() && is_less(&A[largest], &A[r]) {
+            largest = r;
+        }
+        if largest == i {
+            return;
+        }
         A.swap(largest, i);
-        heapify(A, largest);
+        i = largest;
     }
 }
 
-pub fn heap_sort<T: PartialOrd>(A: &mut [T]) {
+// Floyd's variant of `heapify`: follows the larger children down to a
+// leaf with one comparison per level, then climbs back up to where `A[i]`
+// belongs.  Since `A[i]` usually belongs near the bottom, this takes about
+// half the comparisons of `heapify`.
+fn heapify_bottom_up<T, F>(A: &mut [T], i: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let n = A.len();
+    let mut j = i;
+    while right(j) < n {
+        j = if is_less(&A[left(j)], &A[right(j)]) {
+            right(j)
+        } else {
+            left(j)
+        };
+    }
+    if left(j) < n {
+        j = left(j);
+    }
+
+    while is_less(&A[j], &A[i]) {
+        j = parent(j);
+    }
+
+    // Move `A[i]` down to `j`, shifting the path between them up a level.
+    while j > i {
+        A.swap(i, j);
+        j = parent(j);
+    }
+}
+
+// HEAP-INCREASE-KEY, after the key at `A[i]` has been raised.
+pub(crate) fn sift_up<T, F>(A: &mut [T], mut i: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    while i > 0 && is_less(&A[parent(i)], &A[i]) {
+        A.swap(i, parent(i));
+        i = parent(i);
+    }
+}
+
+pub(crate) fn heap_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
+
+    init_heap(A, is_less);
+
+    for i in (1..A.len()).rev() {
+        A.swap(0, i);
+        heapify(&mut A[..i], 0, is_less);
+    }
+}
+
+pub(crate) fn heap_sort_bottom_up_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
     if A.len() <= 1 {
         return;
     }
 
-    init_heap(A);
+    for i in (0..parent(A.len() - 1) + 1).rev() {
+        heapify_bottom_up(A, i, is_less);
+    }
 
     for i in (1..A.len()).rev() {
         A.swap(0, i);
-        heapify(&mut A[..i], 0);
+        heapify_bottom_up(&mut A[..i], 0, is_less);
     }
 }
 
+pub fn heap_sort<T: PartialOrd>(A: &mut [T]) {
+    heap_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn heap_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn heap_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    heap_sort_impl(A, &mut compare::by_key(f));
+}
+
+pub fn heap_sort_bottom_up<T: PartialOrd>(A: &mut [T]) {
+    heap_sort_bottom_up_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn heap_sort_bottom_up_by<T, F>(A: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_sort_bottom_up_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn heap_sort_bottom_up_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    heap_sort_bottom_up_impl(A, &mut compare::by_key(f));
+}
+
 mod tests {
     #[test]
     fn heap_sort() {
@@ -60,4 +197,64 @@ mod tests {
         heap_sort(A.as_mut_slice());
         common::assert_asc(&A);
     }
+
+    #[test]
+    fn heap_sort_by() {
+        use super::heap_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(100);
+        heap_sort_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn heap_sort_by_key() {
+        use super::heap_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(100, 10);
+        heap_sort_by_key(A.as_mut_slice(), |r| r.0);
+        let keys: Vec<_> = A.iter().map(|r| r.0).collect();
+        common::assert_asc(&keys);
+    }
+
+    #[test]
+    fn heap_sort_bottom_up() {
+        use super::{
+            heap_sort,
+            heap_sort_bottom_up,
+        };
+        use crate::common;
+        for &n in &[0, 1, 2, 3, 10, 100, 1000] {
+            let mut A = common::random_vec_range::<i32>(n, -50, 50);
+            let mut B = A.clone();
+            heap_sort(A.as_mut_slice());
+            heap_sort_bottom_up(B.as_mut_slice());
+            assert_eq!(A, B);
+        }
+    }
+
+    #[test]
+    fn heap_sort_bottom_up_comparisons() {
+        use super::{
+            heap_sort_bottom_up_by,
+            heap_sort_by,
+        };
+        use crate::common;
+        let A = common::random_vec::<u64>(10_000);
+
+        let (mut B, mut comparisons) = (A.clone(), 0);
+        heap_sort_by(B.as_mut_slice(), |a, b| {
+            comparisons += 1;
+            a.cmp(b)
+        });
+        let (mut C, mut comparisons_bottom_up) = (A.clone(), 0);
+        heap_sort_bottom_up_by(C.as_mut_slice(), |a, b| {
+            comparisons_bottom_up += 1;
+            a.cmp(b)
+        });
+
+        assert_eq!(B, C);
+        common::assert_asc(&C);
+        assert!(comparisons_bottom_up * 3 < comparisons * 2);
+    }
 }
@@ -0,0 +1,260 @@
+use super::{
+    heapify,
+    init_heap,
+    sift_up,
+};
+use std::cmp::PartialOrd;
+use std::iter::FromIterator;
+use std::marker::PhantomData;
+
+// Which end of the order a `PriorityQueue` keeps at its top.
+pub trait HeapOrder {
+    // Whether `a` belongs below `b` in the heap.
+    fn below<T: PartialOrd>(a: &T, b: &T) -> bool;
+}
+
+pub struct Max;
+
+impl HeapOrder for Max {
+    fn below<T: PartialOrd>(a: &T, b: &T) -> bool {
+        a < b
+    }
+}
+
+pub struct Min;
+
+impl HeapOrder for Min {
+    fn below<T: PartialOrd>(a: &T, b: &T) -> bool {
+        b < a
+    }
+}
+
+// A binary heap keeping its largest (`Max`) or smallest (`Min`) element at
+// the top.
+pub struct PriorityQueue<T, O> {
+    heap: Vec<T>,
+    order: PhantomData<O>,
+}
+
+pub type MaxPriorityQueue<T> = PriorityQueue<T, Max>;
+pub type MinPriorityQueue<T> = PriorityQueue<T, Min>;
+
+impl<T: PartialOrd, O: HeapOrder> PriorityQueue<T, O> {
+    pub fn new() -> PriorityQueue<T, O> {
+        PriorityQueue {
+            heap: Vec::new(),
+            order: PhantomData,
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> PriorityQueue<T, O> {
+        PriorityQueue {
+            heap: Vec::with_capacity(capacity),
+            order: PhantomData,
+        }
+    }
+
+    // BUILD-MAX-HEAP or BUILD-MIN-HEAP, in O(n) time.
+    pub fn from_vec(mut heap: Vec<T>) -> PriorityQueue<T, O> {
+        init_heap(&mut heap, &mut O::below);
+        PriorityQueue {
+            heap,
+            order: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+
+    pub fn insert(&mut self, key: T) {
+        self.heap.push(key);
+        let i = self.heap.len() - 1;
+        sift_up(&mut self.heap, i, &mut O::below);
+    }
+
+    // The elements in heap order.
+    pub fn as_slice(&self) -> &[T] {
+        &self.heap
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.heap
+    }
+
+    fn top(&self) -> Option<&T> {
+        self.heap.first()
+    }
+
+    fn extract_top(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+        let top = self.heap.swap_remove(0);
+        if !self.heap.is_empty() {
+            heapify(&mut self.heap, 0, &mut O::below);
+        }
+        Some(top)
+    }
+
+    // Moves the key at heap position `i` towards the top.
+    fn raise_key(&mut self, i: usize, key: T) {
+        self.heap[i] = key;
+        sift_up(&mut self.heap, i, &mut O::below);
+    }
+
+    // Sorts the heap in place so that the top element ends up last.
+    fn into_sorted(mut self) -> Vec<T> {
+        for i in (1..self.heap.len()).rev() {
+            self.heap.swap(0, i);
+            heapify(&mut self.heap[..i], 0, &mut O::below);
+        }
+        self.heap
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue<T, Max> {
+    pub fn maximum(&self) -> Option<&T> {
+        self.top()
+    }
+
+    pub fn extract_max(&mut self) -> Option<T> {
+        self.extract_top()
+    }
+
+    // Raises the key at heap position `i` to `key`, which must not be
+    // smaller than the current one.
+    pub fn increase_key(&mut self, i: usize, key: T) {
+        assert!(
+            !Max::below(&key, &self.heap[i]),
+            "new key is smaller than current key"
+        );
+        self.raise_key(i, key);
+    }
+
+    // The elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        self.into_sorted()
+    }
+}
+
+impl<T: PartialOrd> PriorityQueue<T, Min> {
+    pub fn minimum(&self) -> Option<&T> {
+        self.top()
+    }
+
+    pub fn extract_min(&mut self) -> Option<T> {
+        self.extract_top()
+    }
+
+    // Lowers the key at heap position `i` to `key`, which must not be
+    // greater than the current one.
+    pub fn decrease_key(&mut self, i: usize, key: T) {
+        assert!(
+            !Min::below(&key, &self.heap[i]),
+            "new key is greater than current key"
+        );
+        self.raise_key(i, key);
+    }
+
+    // The elements in ascending order.
+    pub fn into_sorted_vec(self) -> Vec<T> {
+        let mut sorted = self.into_sorted();
+        sorted.reverse();
+        sorted
+    }
+}
+
+impl<T: PartialOrd, O: HeapOrder> Default for PriorityQueue<T, O> {
+    fn default() -> PriorityQueue<T, O> {
+        PriorityQueue::new()
+    }
+}
+
+impl<T: PartialOrd, O: HeapOrder> From<Vec<T>> for PriorityQueue<T, O> {
+    fn from(heap: Vec<T>) -> PriorityQueue<T, O> {
+        PriorityQueue::from_vec(heap)
+    }
+}
+
+impl<T: PartialOrd, O: HeapOrder> FromIterator<T> for PriorityQueue<T, O> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> PriorityQueue<T, O> {
+        PriorityQueue::from_vec(iter.into_iter().collect())
+    }
+}
+
+mod tests {
+    #[test]
+    fn max_priority_queue() {
+        use super::MaxPriorityQueue;
+        use crate::common;
+        let A = common::random_vec::<i32>(200);
+
+        let mut queue = MaxPriorityQueue::new();
+        for &x in A.iter() {
+            queue.insert(x);
+        }
+        assert_eq!(queue.len(), A.len());
+
+        let mut extracted = Vec::new();
+        while let Some(x) = queue.extract_max() {
+            extracted.push(x);
+        }
+        assert!(queue.maximum().is_none());
+        common::assert_desc(&extracted);
+    }
+
+    #[test]
+    fn max_priority_queue_from_vec() {
+        use super::MaxPriorityQueue;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(200);
+        let queue: MaxPriorityQueue<_> = A.iter().cloned().collect();
+        let sorted = queue.into_sorted_vec();
+        common::assert_asc(&sorted);
+
+        A.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(A, sorted);
+    }
+
+    #[test]
+    fn max_priority_queue_increase_key() {
+        use super::MaxPriorityQueue;
+        let mut queue =
+            MaxPriorityQueue::from_vec(vec![16, 14, 10, 8, 7, 9, 3, 2, 4, 1]);
+        // CLRS figure 6.5
+        let i = queue.as_slice().iter().position(|&x| x == 4).unwrap();
+        queue.increase_key(i, 15);
+        assert_eq!(queue.as_slice(), &[16, 15, 10, 14, 7, 9, 3, 2, 8, 1]);
+        assert_eq!(queue.extract_max(), Some(16));
+        assert_eq!(queue.maximum(), Some(&15));
+    }
+
+    #[test]
+    fn min_priority_queue() {
+        use super::MinPriorityQueue;
+        use crate::common;
+        let A = common::random_vec::<i32>(200);
+
+        let mut queue: MinPriorityQueue<_> = A[..100].iter().cloned().collect();
+        for &x in A[100..].iter() {
+            queue.insert(x);
+        }
+        let i = queue.len() - 1;
+        queue.decrease_key(i, i32::MIN);
+        assert_eq!(queue.minimum(), Some(&i32::MIN));
+
+        let mut extracted = Vec::new();
+        while let Some(x) = queue.extract_min() {
+            extracted.push(x);
+        }
+        common::assert_asc(&extracted);
+
+        let queue = MinPriorityQueue::from_vec(A);
+        common::assert_asc(&queue.into_sorted_vec());
+    }
+}
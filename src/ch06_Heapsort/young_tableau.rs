@@ -0,0 +1,294 @@
+use crate::common::compare;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+// Compares two cells, an empty cell standing for infinity.
+fn cell_less<T, F>(a: &Option<T>, b: &Option<T>, is_less: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    match (a, b) {
+        (Some(a), Some(b)) => is_less(a, b),
+        (Some(_), None) => true,
+        _ => false,
+    }
+}
+
+// An m x n matrix whose rows and columns are sorted, empty cells (infinity)
+// gathering at the bottom right (problem 6-3).
+pub struct YoungTableau<T> {
+    cells: Vec<Option<T>>,
+    m: usize,
+    n: usize,
+    len: usize,
+}
+
+impl<T> YoungTableau<T> {
+    pub fn new(m: usize, n: usize) -> YoungTableau<T> {
+        assert!(m > 0 && n > 0, "tableau must have at least one cell");
+        YoungTableau {
+            cells: (0..m * n).map(|_| None).collect(),
+            m,
+            n,
+            len: 0,
+        }
+    }
+
+    pub fn rows(&self) -> usize {
+        self.m
+    }
+
+    pub fn cols(&self) -> usize {
+        self.n
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.len == self.m * self.n
+    }
+
+    pub fn get(&self, i: usize, j: usize) -> Option<&T> {
+        assert!(i < self.m && j < self.n, "cell out of bounds");
+        self.cells[i * self.n + j].as_ref()
+    }
+
+    pub fn minimum(&self) -> Option<&T> {
+        self.cells[0].as_ref()
+    }
+
+    fn extract_min_by<F>(&mut self, is_less: &mut F) -> Option<T>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        let min = self.cells[0].take()?;
+        self.len -= 1;
+
+        // YOUNGIFY: move the hole at the top left corner down to the bottom
+        // right, pulling up the smaller of its neighbours below and right.
+        let (mut i, mut j) = (0, 0);
+        loop {
+            let mut smallest = (i, j);
+            if i + 1 < self.m
+                && cell_less(
+                    &self.cells[(i + 1) * self.n + j],
+                    &self.cells[smallest.0 * self.n + smallest.1],
+                    is_less,
+                )
+            {
+                smallest = (i + 1, j);
+            }
+            if j + 1 < self.n
+                && cell_less(
+                    &self.cells[i * self.n + j + 1],
+                    &self.cells[smallest.0 * self.n + smallest.1],
+                    is_less,
+                )
+            {
+                smallest = (i, j + 1);
+            }
+            if smallest == (i, j) {
+                return Some(min);
+            }
+            self.cells.swap(i * self.n + j, smallest.0 * self.n + smallest.1);
+            i = smallest.0;
+            j = smallest.1;
+        }
+    }
+
+    fn insert_by<F>(&mut self, key: T, is_less: &mut F) -> Result<(), T>
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if self.is_full() {
+            return Err(key);
+        }
+        self.len += 1;
+
+        // The bottom right cell is empty unless the tableau is full.  Move
+        // the key up and left past the larger of its neighbours above and
+        // left.
+        let (mut i, mut j) = (self.m - 1, self.n - 1);
+        self.cells[i * self.n + j] = Some(key);
+        loop {
+            let mut largest = (i, j);
+            if i > 0
+                && cell_less(
+                    &self.cells[largest.0 * self.n + largest.1],
+                    &self.cells[(i - 1) * self.n + j],
+                    is_less,
+                )
+            {
+                largest = (i - 1, j);
+            }
+            if j > 0
+                && cell_less(
+                    &self.cells[largest.0 * self.n + largest.1],
+                    &self.cells[i * self.n + j - 1],
+                    is_less,
+                )
+            {
+                largest = (i, j - 1);
+            }
+            if largest == (i, j) {
+                return Ok(());
+            }
+            self.cells.swap(i * self.n + j, largest.0 * self.n + largest.1);
+            i = largest.0;
+            j = largest.1;
+        }
+    }
+}
+
+impl<T: PartialOrd> YoungTableau<T> {
+    // O(m + n)
+    pub fn extract_min(&mut self) -> Option<T> {
+        self.extract_min_by(&mut compare::by_partial_ord())
+    }
+
+    // O(m + n), handing `key` back if the tableau is full.
+    pub fn insert(&mut self, key: T) -> Result<(), T> {
+        self.insert_by(key, &mut compare::by_partial_ord())
+    }
+
+    // O(m + n): walks from the top right corner, dropping a column when its
+    // top cell is too large and a row when the cell is too small.
+    pub fn contains(&self, key: &T) -> bool {
+        let (mut i, mut j) = (0, self.n);
+        while i < self.m && j > 0 {
+            match &self.cells[i * self.n + j - 1] {
+                Some(x) if x < key => i += 1,
+                Some(x) if key < x => j -= 1,
+                Some(_) => return true,
+                None => j -= 1,
+            }
+        }
+        false
+    }
+}
+
+// Sorts n^2 elements in O(n^3) time through an n x n tableau.
+pub(crate) fn young_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
+
+    let mut side = (A.len() as f64).sqrt() as usize;
+    while side * side < A.len() {
+        side += 1;
+    }
+
+    let mut tableau = YoungTableau::new(side, side);
+    // The tableau has room for every element.
+    for x in A.iter() {
+        let _ = tableau.insert_by(x.clone(), is_less);
+    }
+    for x in A.iter_mut() {
+        *x = tableau.extract_min_by(is_less).unwrap();
+    }
+}
+
+pub fn young_sort<T: PartialOrd + Clone>(A: &mut [T]) {
+    young_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn young_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    young_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn young_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    young_sort_impl(A, &mut compare::by_key(f));
+}
+
+mod tests {
+    #[test]
+    fn young_tableau() {
+        use super::YoungTableau;
+        let mut T = YoungTableau::new(4, 4);
+        for &x in &[9, 16, 3, 2, 4, 8, 5, 14, 12] {
+            assert_eq!(T.insert(x), Ok(()));
+        }
+        assert_eq!(T.len(), 9);
+        assert_eq!(T.minimum(), Some(&2));
+
+        for i in 0..4 {
+            for j in 0..4 {
+                if let Some(x) = T.get(i, j) {
+                    assert!(i == 0 || T.get(i - 1, j).unwrap() <= x);
+                    assert!(j == 0 || T.get(i, j - 1).unwrap() <= x);
+                }
+            }
+        }
+
+        assert!(T.contains(&14));
+        assert!(T.contains(&2));
+        assert!(!T.contains(&1));
+        assert!(!T.contains(&10));
+        assert!(!T.contains(&17));
+
+        let mut sorted = Vec::new();
+        while let Some(x) = T.extract_min() {
+            sorted.push(x);
+        }
+        assert_eq!(sorted, vec![2, 3, 4, 5, 8, 9, 12, 14, 16]);
+        assert!(T.is_empty());
+    }
+
+    #[test]
+    fn young_tableau_full() {
+        use super::YoungTableau;
+        let mut T = YoungTableau::new(2, 3);
+        for x in (0..6).rev() {
+            assert_eq!(T.insert(x), Ok(()));
+        }
+        assert!(T.is_full());
+        assert_eq!(T.insert(6), Err(6));
+        assert_eq!(T.extract_min(), Some(0));
+        assert_eq!(T.insert(6), Ok(()));
+    }
+
+    #[test]
+    fn young_sort() {
+        use super::young_sort;
+        use crate::common;
+        for &n in &[1, 2, 10, 16, 17, 1000] {
+            let mut A = common::random_vec_range::<i32>(n, -100, 100);
+            young_sort(&mut A);
+            assert_eq!(A.len(), n);
+            common::assert_asc(&A);
+        }
+    }
+
+    #[test]
+    fn young_sort_by_key() {
+        use super::young_sort_by_key;
+        // No `PartialOrd` on the elements themselves.
+        #[derive(Clone, Debug, PartialEq)]
+        struct Job(&'static str, u32);
+
+        let mut A = vec![Job("b", 3), Job("a", 1), Job("c", 2)];
+        young_sort_by_key(&mut A, |job| job.1);
+        assert_eq!(A, vec![Job("a", 1), Job("c", 2), Job("b", 3)]);
+    }
+}
@@ -0,0 +1,241 @@
+use super::partition3_by_impl;
+use crate::{
+    ch02_Getting_Start::insertion_sort::insertion_sort_by_swap_impl,
+    common::compare,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// Sorts five elements spaced a seventh of `A` apart around its middle, and
+// moves the second and fourth to both ends as the pivots, as the JDK does.
+// Sorted or reverse-sorted input then splits into even thirds rather than
+// shedding two elements per partition.
+fn choose_pivots<T, F>(A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (mid, seventh) = (A.len() / 2, A.len() / 7);
+    let e = [
+        mid - 2 * seventh,
+        mid - seventh,
+        mid,
+        mid + seventh,
+        mid + 2 * seventh,
+    ];
+    for i in 1..e.len() {
+        let mut j = i;
+        while j > 0 && is_less(&A[e[j]], &A[e[j - 1]]) {
+            A.swap(e[j], e[j - 1]);
+            j -= 1;
+        }
+    }
+
+    let last = A.len() - 1;
+    A.swap(0, e[1]);
+    A.swap(last, e[3]);
+}
+
+// Yaroslavskiy's partition around the pivots `p = A[0]` and `q = A[last]`.
+// Returns `(l, g)`, the final positions of both pivots, such that
+//   A[..l] < p <= A[l + 1..g] <= q < A[g + 1..]
+fn dual_partition<T, F>(A: &mut [T], is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let last = A.len() - 1;
+    if is_less(&A[last], &A[0]) {
+        A.swap(0, last);
+    }
+
+    let (mut l, mut g, mut k) = (1, last - 1, 1);
+    while k <= g {
+        if is_less(&A[k], &A[0]) {
+            A.swap(k, l);
+            l += 1;
+        } else if !is_less(&A[k], &A[last]) {
+            while k < g && is_less(&A[last], &A[g]) {
+                g -= 1;
+            }
+            A.swap(k, g);
+            g -= 1;
+            if is_less(&A[k], &A[0]) {
+                A.swap(k, l);
+                l += 1;
+            }
+        }
+        k += 1;
+    }
+    l -= 1;
+    g += 1;
+
+    A.swap(0, l);
+    A.swap(last, g);
+    (l, g)
+}
+
+// Moves the keys equal to either pivot out of the middle part
+// `A[l + 1..g]`, and returns the range that is left to be sorted.
+fn squeeze_middle<T, F>(
+    A: &mut [T],
+    l: usize,
+    g: usize,
+    is_less: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // Everything in the middle satisfies `p <= x <= q`, so one comparison
+    // against each pivot is enough to detect equality.
+    let (mut lo, mut hi, mut k) = (l + 1, g, l + 1);
+    while k < hi {
+        if !is_less(&A[l], &A[k]) {
+            A.swap(k, lo);
+            lo += 1;
+        } else if !is_less(&A[k], &A[g]) {
+            while k + 1 < hi && !is_less(&A[hi - 1], &A[g]) {
+                hi -= 1;
+            }
+            hi -= 1;
+            A.swap(k, hi);
+            if !is_less(&A[l], &A[k]) {
+                A.swap(k, lo);
+                lo += 1;
+            }
+        }
+        k += 1;
+    }
+    (lo, hi)
+}
+
+pub(crate) fn dual_pivot_quick_sort_impl<T, F>(mut A: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    while A.len() > INSERTION_SORT_THRESHOLD {
+        choose_pivots(A, is_less);
+        if !is_less(&A[0], &A[A.len() - 1]) && !is_less(&A[A.len() - 1], &A[0])
+        {
+            // Both pivots are equal: a single-pivot three-way partition
+            // moves the whole run of equal keys out of the way at once.
+            let (lt, gt) = partition3_by_impl(A, 0, is_less);
+            let (left, right) = A.split_at_mut(lt);
+            let right = &mut right[gt - lt..];
+            if left.len() < right.len() {
+                dual_pivot_quick_sort_impl(left, is_less);
+                A = right;
+            } else {
+                dual_pivot_quick_sort_impl(right, is_less);
+                A = left;
+            }
+            continue;
+        }
+
+        let (l, g) = dual_partition(A, is_less);
+        let (lo, hi) = squeeze_middle(A, l, g, is_less);
+
+        // Recurse into the two smaller parts and loop on the largest one.
+        let (left, rest) = A.split_at_mut(l);
+        let (middle, right) = rest.split_at_mut(g - l);
+        let middle = &mut middle[lo - l..hi - l];
+        let right = &mut right[1..];
+
+        let mut parts = [left, middle, right];
+        parts.sort_by_key(|part| part.len());
+        let [small, medium, large] = parts;
+        dual_pivot_quick_sort_impl(small, is_less);
+        dual_pivot_quick_sort_impl(medium, is_less);
+        A = large;
+    }
+
+    insertion_sort_by_swap_impl(A, is_less);
+}
+
+pub fn dual_pivot_quick_sort<T: PartialOrd>(A: &mut [T]) {
+    dual_pivot_quick_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn dual_pivot_quick_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    dual_pivot_quick_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn dual_pivot_quick_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    dual_pivot_quick_sort_impl(A, &mut compare::by_key(f));
+}
+
+mod tests {
+    #[test]
+    fn dual_pivot_quick_sort() {
+        use super::dual_pivot_quick_sort;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(1000);
+        dual_pivot_quick_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn dual_pivot_quick_sort_presorted() {
+        use super::dual_pivot_quick_sort;
+        use crate::common;
+        let mut A: Vec<u32> = (0..10_000).collect();
+        dual_pivot_quick_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A: Vec<u32> = (0..10_000).rev().collect();
+        dual_pivot_quick_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn dual_pivot_quick_sort_comparisons() {
+        use super::dual_pivot_quick_sort_by;
+        use crate::common;
+
+        let n: u64 = 100_000;
+        let bound = 3 * n * (64 - n.leading_zeros()) as u64;
+        let inputs: Vec<Vec<u64>> =
+            vec![(0..n).collect(), (0..n).rev().collect()];
+        for mut A in inputs {
+            let mut comparisons = 0;
+            dual_pivot_quick_sort_by(A.as_mut_slice(), |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            common::assert_asc(&A);
+            assert!(comparisons <= bound, "{} comparisons", comparisons);
+        }
+    }
+
+    #[test]
+    fn dual_pivot_quick_sort_duplicates() {
+        use super::dual_pivot_quick_sort;
+        use crate::common;
+        let mut A = common::random_vec_range::<u8>(100_000, 0, 4);
+        dual_pivot_quick_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A = vec![3; 10_000];
+        dual_pivot_quick_sort(A.as_mut_slice());
+        assert!(A.iter().all(|&x| x == 3));
+    }
+
+    #[test]
+    fn dual_pivot_quick_sort_by_key() {
+        use super::dual_pivot_quick_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(1000, 20);
+        dual_pivot_quick_sort_by_key(A.as_mut_slice(), |r| r.0);
+        let keys: Vec<_> = A.iter().map(|r| r.0).collect();
+        common::assert_asc(&keys);
+    }
+}
@@ -0,0 +1,126 @@
+use super::{
+    median_of_three,
+    partition_by_impl,
+};
+use crate::{
+    ch02_Getting_Start::insertion_sort::insertion_sort_impl,
+    ch06_Heapsort::heap_sort_impl,
+    common::compare,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+#[inline]
+fn depth_limit(n: usize) -> usize {
+    // 2 * floor(lg n)
+    2 * (8 * std::mem::size_of::<usize>() - 1 - n.leading_zeros() as usize)
+}
+
+fn intro_sort_loop<T, F>(mut A: &mut [T], mut depth: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    while A.len() > INSERTION_SORT_THRESHOLD {
+        if depth == 0 {
+            heap_sort_impl(A, is_less);
+            return;
+        }
+        depth -= 1;
+
+        let pivot = median_of_three(A, 0, A.len() / 2, A.len() - 1, is_less);
+        let q = partition_by_impl(A, pivot, is_less);
+        let (left, right) = A.split_at_mut(q);
+        let right = &mut right[1..];
+
+        if left.len() < right.len() {
+            intro_sort_loop(left, depth, is_less);
+            A = right;
+        } else {
+            intro_sort_loop(right, depth, is_less);
+            A = left;
+        }
+    }
+}
+
+pub(crate) fn intro_sort_impl<T, F>(A: &mut [T], is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    if A.len() <= 1 {
+        return;
+    }
+
+    intro_sort_loop(A, depth_limit(A.len()), is_less);
+
+    // Every slice left behind by `intro_sort_loop` holds at most
+    // `INSERTION_SORT_THRESHOLD` elements that all belong there, so one
+    // insertion sort pass over the whole array finishes in O(n) time.
+    insertion_sort_impl(A, is_less);
+}
+
+pub fn intro_sort<T: PartialOrd + Clone>(A: &mut [T]) {
+    intro_sort_impl(A, &mut compare::by_partial_ord());
+}
+
+pub fn intro_sort_by<T, F>(A: &mut [T], compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    intro_sort_impl(A, &mut compare::by_ordering(compare));
+}
+
+pub fn intro_sort_by_key<T, K, F>(A: &mut [T], f: F)
+where
+    T: Clone,
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    intro_sort_impl(A, &mut compare::by_key(f));
+}
+
+mod tests {
+    #[test]
+    fn intro_sort() {
+        use super::intro_sort;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(1000);
+        intro_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn intro_sort_presorted() {
+        use super::intro_sort;
+        use crate::common;
+        let mut A: Vec<u32> = (0..200_000).collect();
+        intro_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A: Vec<u32> = (0..200_000).rev().collect();
+        intro_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn intro_sort_all_equal() {
+        use super::intro_sort;
+        let mut A = vec![7; 10_000];
+        intro_sort(A.as_mut_slice());
+        assert!(A.iter().all(|&x| x == 7));
+    }
+
+    #[test]
+    fn intro_sort_by() {
+        use super::intro_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(1000);
+        intro_sort_by(A.as_mut_slice(), |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+}
@@ -1,9 +1,19 @@
 extern crate rand;
 
+pub mod dual_pivot_quick_sort;
+pub mod intro_sort;
+
+use crate::common::compare;
 use rand::Rng;
-use std::cmp::PartialOrd;
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
 
-fn partition<T: PartialOrd>(A: &mut [T]) -> usize {
+fn partition<T, F>(A: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
     // `partition` fails if `A` has less than 2 elements.
     // Caller should avoid this.
     assert!(A.len() > 1);
@@ -15,9 +25,20 @@ fn partition<T: PartialOrd>(A: &mut [T]) -> usize {
     let last_index = A.len() - 1;
 
     let mut i: usize = 0;
+    let mut tie_to_left = false;
 
     for j in 0..A.len() - 1 {
-        if A[j] <= A[last_index] {
+        let to_left = if is_less(&A[j], &A[last_index]) {
+            true
+        } else if is_less(&A[last_index], &A[j]) {
+            false
+        } else {
+            // Exercise 7.1-2: elements equal to the pivot alternate between
+            // both sides, so that an all-equal array is split in the middle.
+            tie_to_left = !tie_to_left;
+            tie_to_left
+        };
+        if to_left {
             A.swap(i, j);
             i += 1;
         }
@@ -26,33 +47,272 @@ fn partition<T: PartialOrd>(A: &mut [T]) -> usize {
     i
 }
 
-pub fn partition_by<T: PartialOrd>(A: &mut [T], i: usize) -> usize {
+pub(crate) fn partition_by_impl<T, F>(
+    A: &mut [T],
+    i: usize,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
     A.swap(i, A.len() - 1);
-    partition(A)
+    partition(A, is_less)
+}
+
+pub(crate) fn randomized_partition_impl<T, F>(
+    A: &mut [T],
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut rnd = rand::thread_rng();
+    let q = rnd.gen_range(0, A.len());
+    partition_by_impl(A, q, is_less)
+}
+
+pub fn partition_by<T: PartialOrd>(A: &mut [T], i: usize) -> usize {
+    partition_by_impl(A, i, &mut compare::by_partial_ord())
 }
 
 pub fn randomized_partition<T: PartialOrd>(A: &mut [T]) -> usize {
+    randomized_partition_impl(A, &mut compare::by_partial_ord())
+}
+
+fn partition3<T, F>(A: &mut [T], is_less: &mut F) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    assert!(A.len() > 1);
+
+    // Dijkstra's Dutch national flag partition, with the pivot parked at
+    // the last position.  Invariants during the scan:
+    //   A[..lt] < pivot, A[lt..i] == pivot, A[gt..last_index] > pivot
+    let last_index = A.len() - 1;
+
+    let (mut lt, mut i, mut gt) = (0, 0, last_index);
+
+    while i < gt {
+        if is_less(&A[i], &A[last_index]) {
+            A.swap(lt, i);
+            lt += 1;
+            i += 1;
+        } else if is_less(&A[last_index], &A[i]) {
+            gt -= 1;
+            A.swap(i, gt);
+        } else {
+            i += 1;
+        }
+    }
+    A.swap(gt, last_index);
+    (lt, gt + 1)
+}
+
+pub(crate) fn partition3_by_impl<T, F>(
+    A: &mut [T],
+    i: usize,
+    is_less: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    A.swap(i, A.len() - 1);
+    partition3(A, is_less)
+}
+
+pub(crate) fn randomized_partition3_impl<T, F>(
+    A: &mut [T],
+    is_less: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
     let mut rnd = rand::thread_rng();
     let q = rnd.gen_range(0, A.len());
-    partition_by(A, q)
+    partition3_by_impl(A, q, is_less)
+}
+
+// Returns `(lt, gt)` such that `A[..lt]` is less than, `A[lt..gt]` is
+// equal to and `A[gt..]` is greater than the pivot.
+pub fn partition3_by<T: PartialOrd>(A: &mut [T], i: usize) -> (usize, usize) {
+    partition3_by_impl(A, i, &mut compare::by_partial_ord())
+}
+
+pub fn randomized_partition3<T: PartialOrd>(A: &mut [T]) -> (usize, usize) {
+    randomized_partition3_impl(A, &mut compare::by_partial_ord())
 }
 
-#[derive(Copy, Clone)]
-pub enum PartitionBy {
+fn ninther<T, F>(A: &[T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (last, mid, step) = (A.len() - 1, A.len() / 2, A.len() / 8);
+    if step == 0 {
+        return median_of_three(A, 0, mid, last, is_less);
+    }
+
+    let a = median_of_three(A, 0, step, 2 * step, is_less);
+    let b = median_of_three(A, mid - step, mid, mid + step, is_less);
+    let c = median_of_three(A, last - 2 * step, last - step, last, is_less);
+    median_of_three(A, a, b, c, is_less)
+}
+
+pub enum PartitionBy<T> {
     Last,
     Random,
+    ThreeWay,
+    MedianOfThree,
+    Ninther,
+    Custom(fn(&[T]) -> usize),
 }
 
-pub fn quick_sort<T: PartialOrd>(A: &mut [T], partition_type: PartitionBy) {
-    if A.len() <= 1 {
-        return;
+// `#[derive]` would require `T: Copy`, which the function pointer in
+// `Custom` does not need.
+impl<T> Copy for PartitionBy<T> {}
+
+impl<T> Clone for PartitionBy<T> {
+    fn clone(&self) -> PartitionBy<T> {
+        *self
     }
-    let q = match partition_type {
-        PartitionBy::Last => partition(A),
-        PartitionBy::Random => randomized_partition(A),
+}
+
+pub(crate) fn median_of_three<T, F>(
+    A: &[T],
+    a: usize,
+    b: usize,
+    c: usize,
+    is_less: &mut F,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (a, b) = if is_less(&A[b], &A[a]) {
+        (b, a)
+    } else {
+        (a, b)
     };
-    quick_sort(&mut A[..q], partition_type);
-    quick_sort(&mut A[q..], partition_type);
+    if is_less(&A[c], &A[a]) {
+        a
+    } else if is_less(&A[c], &A[b]) {
+        c
+    } else {
+        b
+    }
+}
+
+// Partitions `A` once, returning `(lt, gt)` such that `A[lt..gt]` holds
+// keys equal to the pivot, already in their final positions.
+pub(crate) fn partition_step<T, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    is_less: &mut F,
+) -> (usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    match partition_type {
+        PartitionBy::Last => {
+            let q = partition(A, is_less);
+            (q, q + 1)
+        }
+        PartitionBy::Random => {
+            let q = randomized_partition_impl(A, is_less);
+            (q, q + 1)
+        }
+        PartitionBy::ThreeWay => randomized_partition3_impl(A, is_less),
+        PartitionBy::MedianOfThree => {
+            // The quartiles rather than both ends and the middle, which an
+            // organ-pipe input (smallest keys at both ends, largest in the
+            // middle) would turn into a quadratic run of lopsided splits.
+            let n = A.len();
+            let i = median_of_three(A, n / 4, n / 2, n - 1 - n / 4, is_less);
+            let q = partition_by_impl(A, i, is_less);
+            (q, q + 1)
+        }
+        PartitionBy::Ninther => {
+            let i = ninther(A, is_less);
+            let q = partition_by_impl(A, i, is_less);
+            (q, q + 1)
+        }
+        PartitionBy::Custom(choose) => {
+            let i = choose(A);
+            assert!(i < A.len());
+            let q = partition_by_impl(A, i, is_less);
+            (q, q + 1)
+        }
+    }
+}
+
+// Returns the depth of the recursion tree, counting partition steps.
+fn quick_sort_loop<T, F>(
+    mut A: &mut [T],
+    partition_type: PartitionBy<T>,
+    is_less: &mut F,
+    mut depth: usize,
+) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut max_depth = depth;
+
+    while A.len() > 1 {
+        depth += 1;
+        let (lt, gt) = partition_step(A, partition_type, is_less);
+        let (left, right) = A.split_at_mut(lt);
+        let right = &mut right[gt - lt..];
+
+        // Recurse into the smaller side and loop on the larger one,
+        // so that the stack depth is O(log n) whatever the pivots are.
+        let sub_depth = if left.len() < right.len() {
+            let sub_depth =
+                quick_sort_loop(left, partition_type, is_less, depth);
+            A = right;
+            sub_depth
+        } else {
+            let sub_depth =
+                quick_sort_loop(right, partition_type, is_less, depth);
+            A = left;
+            sub_depth
+        };
+        max_depth = max_depth.max(sub_depth).max(depth);
+    }
+
+    max_depth
+}
+
+pub(crate) fn quick_sort_impl<T, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    quick_sort_loop(A, partition_type, is_less, 0);
+}
+
+pub fn quick_sort<T: PartialOrd>(A: &mut [T], partition_type: PartitionBy<T>) {
+    quick_sort_impl(A, partition_type, &mut compare::by_partial_ord());
+}
+
+pub fn quick_sort_by<T, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    compare: F,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_sort_impl(A, partition_type, &mut compare::by_ordering(compare));
+}
+
+pub fn quick_sort_by_key<T, K, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    f: F,
+) where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    quick_sort_impl(A, partition_type, &mut compare::by_key(f));
 }
 
 mod tests {
@@ -74,4 +334,134 @@ mod tests {
         common::assert_asc(&A);
     }
 
+    #[test]
+    fn quick_sort_by() {
+        use super::*;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(100);
+        quick_sort_by(A.as_mut_slice(), PartitionBy::Random, |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn quick_sort_by_key() {
+        use super::*;
+        use crate::common;
+        let mut A = common::random_records(100, 10);
+        quick_sort_by_key(A.as_mut_slice(), PartitionBy::Last, |r| r.0);
+        let keys: Vec<_> = A.iter().map(|r| r.0).collect();
+        common::assert_asc(&keys);
+    }
+
+    #[test]
+    fn quick_sort_presorted() {
+        use super::*;
+        use crate::common;
+        let mut A: Vec<u32> = (0..5_000).collect();
+        quick_sort(A.as_mut_slice(), PartitionBy::Last);
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn partition3() {
+        use super::*;
+        use crate::common;
+        let mut A = common::random_vec_range::<i32>(200, 0, 5);
+        let (lt, gt) = partition3_by(A.as_mut_slice(), 17);
+        assert!(lt < gt);
+        assert!(A[..lt].iter().all(|&x| x < A[lt]));
+        assert!(A[lt..gt].iter().all(|&x| x == A[lt]));
+        assert!(A[gt..].iter().all(|&x| x > A[lt]));
+    }
+
+    #[test]
+    fn partition_ties() {
+        use super::partition;
+        use crate::common::compare;
+        for &n in &[2, 3, 10, 101] {
+            let mut A = vec![7; n];
+            let q = partition(A.as_mut_slice(), &mut compare::by_partial_ord());
+            assert_eq!(q, n / 2);
+        }
+    }
+
+    #[test]
+    fn quick_sort_three_way() {
+        use super::*;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(100);
+        quick_sort(A.as_mut_slice(), PartitionBy::ThreeWay);
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec_range::<u16>(100_000, 200, 204);
+        quick_sort(A.as_mut_slice(), PartitionBy::ThreeWay);
+        common::assert_asc(&A);
+    }
+
+    fn depth_of<T: PartialOrd>(A: &mut [T], p: super::PartitionBy<T>) -> usize {
+        use crate::common::compare;
+        super::quick_sort_loop(A, p, &mut compare::by_partial_ord(), 0)
+    }
+
+    fn inputs(n: u32) -> Vec<(&'static str, Vec<u32>)> {
+        vec![
+            ("sorted", (0..n).collect()),
+            ("reverse-sorted", (0..n).rev().collect()),
+            (
+                "organ-pipe",
+                (0..n)
+                    .map(|i| {
+                        if i < n / 2 {
+                            i
+                        } else {
+                            n - 1 - i
+                        }
+                    })
+                    .collect(),
+            ),
+            ("all-equal", vec![42; n as usize]),
+        ]
+    }
+
+    #[test]
+    fn quick_sort_pivot_strategies_depth() {
+        use super::*;
+        use crate::common;
+
+        fn middle(A: &[u32]) -> usize {
+            A.len() / 2
+        }
+
+        let n: u32 = 10_000;
+        let max_depth = 3 * (32 - n.leading_zeros()) as usize;
+
+        for (name, input) in inputs(n) {
+            let mut strategies =
+                vec![PartitionBy::MedianOfThree, PartitionBy::Ninther];
+            // The middle of an organ pipe is its maximum, so `middle` makes
+            // no promise there.
+            if name != "organ-pipe" {
+                strategies.push(PartitionBy::Custom(middle));
+            }
+            for p in strategies {
+                let mut A = input.clone();
+                let depth = depth_of(A.as_mut_slice(), p);
+                common::assert_asc(&A);
+                assert!(depth <= max_depth, "{}: depth {}", name, depth);
+            }
+        }
+    }
+
+    #[test]
+    fn quick_sort_ninther() {
+        use super::*;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(1000);
+        quick_sort(A.as_mut_slice(), PartitionBy::Ninther);
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec::<f64>(1000);
+        quick_sort(A.as_mut_slice(), PartitionBy::MedianOfThree);
+        common::assert_asc(&A);
+    }
 }
@@ -1,57 +1,193 @@
-use num::{
-    Bounded,
-    Num,
+extern crate rand;
+
+use crate::ch02_Getting_Start::{
+    insertion_sort::insertion_sort_impl,
+    merge_sort::merge_sort_impl,
+};
+use rand::Rng;
+use std::{
+    error::Error,
+    fmt,
 };
-use std::cmp::PartialOrd;
 
-pub fn bucket_sort<T>(A: &mut [T])
-where
-    T: Bounded + Num + PartialOrd + Copy,
-    T: Into<f64>,
-{
-    use crate::ch02_Getting_Start::insertion_sort::insertion_sort;
-
-    let (margin, min, max) = {
-        let mut min = T::max_value();
-        let mut max = T::min_value();
-
-        for &x in A.iter() {
-            if x < min {
-                min = x
-            }
-            if x > max {
-                max = x
-            }
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BucketSortError {
+    // The key of the element at `index` is NaN, which has no place in the
+    // order.
+    NaNKey {
+        index: usize,
+    },
+}
+
+impl fmt::Display for BucketSortError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BucketSortError::NaNKey {
+                index,
+            } => write!(f, "key of element {} is NaN", index),
         }
+    }
+}
 
-        ((max - min).into() / A.len() as f64, min, max)
-    };
+impl Error for BucketSortError {}
 
-    if min == max {
-        return;
+pub struct BucketSortConfig {
+    // Number of buckets, one per element if `None`.
+    pub n_buckets: Option<usize>,
+    // Number of keys sampled to estimate their distribution.
+    pub sample_size: usize,
+}
+
+impl Default for BucketSortConfig {
+    fn default() -> BucketSortConfig {
+        BucketSortConfig {
+            n_buckets: None,
+            sample_size: 256,
+        }
     }
+}
 
-    let mut buckets: Vec<Vec<T>> = Vec::with_capacity(A.len() + 1);
-    for _ in 0..=A.len() {
-        buckets.push(Vec::new());
+// Maps keys to buckets along a piecewise linear estimate of their
+// distribution.  The sorted, distinct sample keys (with the minimum and the
+// maximum) split the range into intervals holding about as many keys each,
+// and the buckets are dealt out evenly to the intervals, so that skewed keys
+// still spread over all buckets.  With fewer buckets than intervals, some
+// intervals get none and share a bucket with the next one.
+struct Boundaries {
+    points: Vec<f64>,
+    n_buckets: usize,
+}
+
+impl Boundaries {
+    fn new(
+        keys: &[f64],
+        min: f64,
+        max: f64,
+        config: &BucketSortConfig,
+    ) -> Boundaries {
+        let mut rnd = rand::thread_rng();
+        let mut points: Vec<f64> = (0..config.sample_size.min(keys.len()))
+            .map(|_| keys[rnd.gen_range(0, keys.len())])
+            .collect();
+        points.push(min);
+        points.push(max);
+        merge_sort_impl(&mut points, &mut |a: &f64, b: &f64| a < b);
+        points.dedup();
+
+        Boundaries {
+            points,
+            n_buckets: config.n_buckets.unwrap_or(keys.len()).max(1),
+        }
     }
 
-    for &x in A.iter() {
-        let bucket_id = ((x - min).into() / margin) as usize;
-        buckets[bucket_id].push(x);
+    fn n_buckets(&self) -> usize {
+        self.n_buckets
     }
 
-    for bucket in buckets.iter_mut() {
-        insertion_sort(bucket);
+    // The first bucket of the interval [points[i], points[i + 1]).
+    fn first_bucket(&self, i: usize) -> usize {
+        i * self.n_buckets / (self.points.len() - 1)
+    }
+
+    fn bucket(&self, key: f64) -> usize {
+        // The interval [points[i], points[i + 1]) holding `key`, the maximum
+        // going into the last one.
+        let i = match self
+            .points
+            .binary_search_by(|p| p.partial_cmp(&key).unwrap())
+        {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        }
+        .min(self.points.len() - 2);
+
+        // Infinite bounds make this NaN, which casts to bucket 0.
+        let t = (key - self.points[i]) / (self.points[i + 1] - self.points[i]);
+        let first = self.first_bucket(i);
+        let width = self.first_bucket(i + 1) - first;
+        first + ((t * width as f64) as usize).min(width.saturating_sub(1))
+    }
+}
+
+// Bucket sort by an `f64` key, stable.  Buckets of at most
+// `INSERTION_SORT_THRESHOLD` elements are finished with insertion sort and
+// larger ones with merge sort, so the running time degrades to O(n lg n)
+// rather than O(n^2) when the sample misjudges the distribution.
+pub fn bucket_sort_with_config<T, F>(
+    A: &mut [T],
+    key: F,
+    config: &BucketSortConfig,
+) -> Result<(), BucketSortError>
+where
+    T: Clone,
+    F: Fn(&T) -> f64,
+{
+    let keys: Vec<f64> = A.iter().map(key).collect();
+    if let Some(index) = keys.iter().position(|k| k.is_nan()) {
+        return Err(BucketSortError::NaNKey {
+            index,
+        });
+    }
+    if A.len() <= 1 {
+        return Ok(());
+    }
+
+    let min = keys.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = keys.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    if min == max {
+        return Ok(());
+    }
+
+    let boundaries = Boundaries::new(&keys, min, max, config);
+    let buckets: Vec<usize> =
+        keys.iter().map(|&k| boundaries.bucket(k)).collect();
+
+    // Distribute the indices of the elements, keeping their order within
+    // every bucket.  C[b] ends up as the first position of bucket `b`.
+    let mut C = vec![0usize; boundaries.n_buckets() + 1];
+    for &b in &buckets {
+        C[b + 1] += 1;
+    }
+    for b in 1..C.len() {
+        C[b] += C[b - 1];
+    }
+    let mut next = C.clone();
+    let mut order = vec![0usize; A.len()];
+    for (i, &b) in buckets.iter().enumerate() {
+        order[next[b]] = i;
+        next[b] += 1;
     }
 
-    let mut i = 0;
-    for bucket in buckets.iter() {
-        for &x in bucket.iter() {
-            A[i] = x;
-            i += 1;
+    let mut is_less = |&i: &usize, &j: &usize| keys[i] < keys[j];
+    for b in 0..boundaries.n_buckets() {
+        let bucket = &mut order[C[b]..C[b + 1]];
+        if bucket.len() <= INSERTION_SORT_THRESHOLD {
+            insertion_sort_impl(bucket, &mut is_less);
+        } else {
+            merge_sort_impl(bucket, &mut is_less);
         }
     }
+
+    let sorted: Vec<T> = order.iter().map(|&i| A[i].clone()).collect();
+    A.clone_from_slice(&sorted);
+    Ok(())
+}
+
+pub fn bucket_sort_by<T, F>(A: &mut [T], key: F) -> Result<(), BucketSortError>
+where
+    T: Clone,
+    F: Fn(&T) -> f64,
+{
+    bucket_sort_with_config(A, key, &BucketSortConfig::default())
+}
+
+pub fn bucket_sort<T>(A: &mut [T]) -> Result<(), BucketSortError>
+where
+    T: Copy + Into<f64>,
+{
+    bucket_sort_by(A, |&x| x.into())
 }
 
 mod tests {
@@ -66,7 +202,108 @@ mod tests {
             Bounded::min_value(),
             Bounded::max_value(),
         );
-        bucket_sort(A.as_mut_slice());
+        bucket_sort(A.as_mut_slice()).unwrap();
         common::assert_asc(&A);
+
+        let mut A: Vec<f32> = vec![];
+        bucket_sort(A.as_mut_slice()).unwrap();
+        let mut A = vec![1u32];
+        bucket_sort(A.as_mut_slice()).unwrap();
+        assert_eq!(A, vec![1]);
+
+        let mut A = vec![3.0, f64::INFINITY, -1.0, f64::NEG_INFINITY, 3.0, 0.5];
+        bucket_sort(A.as_mut_slice()).unwrap();
+        assert_eq!(
+            A,
+            vec![f64::NEG_INFINITY, -1.0, 0.5, 3.0, 3.0, f64::INFINITY]
+        );
+    }
+
+    #[test]
+    fn bucket_sort_nan() {
+        use super::{
+            bucket_sort,
+            BucketSortError,
+        };
+        let mut A = vec![1.0, 2.0, f64::NAN, 0.0];
+        assert_eq!(
+            bucket_sort(A.as_mut_slice()),
+            Err(BucketSortError::NaNKey {
+                index: 2
+            })
+        );
+        // The slice is left untouched.
+        assert_eq!(A[..2], [1.0, 2.0]);
+        assert!(A[2].is_nan());
+        assert_eq!(A[3], 0.0);
+    }
+
+    #[test]
+    fn bucket_sort_skewed() {
+        use super::bucket_sort_by;
+        use crate::common;
+
+        // Mostly tiny keys with a few huge outliers, which would crowd almost
+        // everything into the first of evenly spaced buckets.
+        let mut A: Vec<f64> = common::random_vec_range::<f64>(5000, 0.0, 30.0)
+            .into_iter()
+            .map(|x| x.exp())
+            .collect();
+        A.extend(vec![1e300, 1e299, -1e300]);
+        let mut B = A.clone();
+        bucket_sort_by(A.as_mut_slice(), |&x| x).unwrap();
+        B.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(A, B);
+    }
+
+    #[test]
+    fn bucket_sort_stable() {
+        use super::{
+            bucket_sort_with_config,
+            BucketSortConfig,
+        };
+        use crate::common;
+
+        for &n_buckets in &[None, Some(1), Some(3), Some(10_000)] {
+            let config = BucketSortConfig {
+                n_buckets,
+                ..BucketSortConfig::default()
+            };
+            let mut A = common::random_records(1000, 50);
+            bucket_sort_with_config(A.as_mut_slice(), |r| r.0 as f64, &config)
+                .unwrap();
+            common::assert_stable(&A);
+        }
+    }
+
+    #[test]
+    fn bucket_sort_n_buckets() {
+        use super::{
+            Boundaries,
+            BucketSortConfig,
+        };
+        use crate::common;
+
+        let keys = common::random_vec_range::<f64>(1000, 0.0, 1.0);
+        let min = keys.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = keys.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let mut sorted = keys.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for &n_buckets in &[1, 3, 100, 300, 10_000] {
+            let config = BucketSortConfig {
+                n_buckets: Some(n_buckets),
+                ..BucketSortConfig::default()
+            };
+            let boundaries = Boundaries::new(&keys, min, max, &config);
+            assert_eq!(boundaries.n_buckets(), n_buckets);
+
+            // Buckets follow the order of the keys and the extremes land in
+            // the first and the last one.
+            let buckets: Vec<usize> =
+                sorted.iter().map(|&k| boundaries.bucket(k)).collect();
+            assert!(buckets.windows(2).all(|w| w[0] <= w[1]));
+            assert_eq!(buckets[0], 0);
+            assert_eq!(buckets[buckets.len() - 1], n_buckets - 1);
+        }
     }
 }
@@ -1,3 +1,51 @@
+mod traits {
+    // Primitive integers that counting sort can index by their distance from
+    // the minimum of the input.
+    pub trait CountingKey: Copy + Ord {
+        fn offset_from(self, min: Self) -> u64;
+    }
+
+    macro_rules! impl_counting_key_unsigned {
+        ($t:ty) => {
+            impl CountingKey for $t {
+                #[inline]
+                fn offset_from(self, min: $t) -> u64 {
+                    self as u64 - min as u64
+                }
+            }
+        };
+    }
+
+    // Two's complement makes the wrapped difference exact whenever
+    // `self >= min`, even across the full range of `i64`.
+    macro_rules! impl_counting_key_signed {
+        ($t:ty) => {
+            impl CountingKey for $t {
+                #[inline]
+                fn offset_from(self, min: $t) -> u64 {
+                    (self as i64).wrapping_sub(min as i64) as u64
+                }
+            }
+        };
+    }
+
+    impl_counting_key_unsigned!(u8);
+    impl_counting_key_unsigned!(u16);
+    impl_counting_key_unsigned!(u32);
+    impl_counting_key_unsigned!(u64);
+    impl_counting_key_unsigned!(usize);
+
+    impl_counting_key_signed!(i8);
+    impl_counting_key_signed!(i16);
+    impl_counting_key_signed!(i32);
+    impl_counting_key_signed!(i64);
+    impl_counting_key_signed!(isize);
+}
+
+pub use traits::CountingKey;
+
+use crate::ch02_Getting_Start::merge_sort::merge_sort;
+
 pub fn counting_sort(A: &mut [i32], lower: i32, upper: i32) {
     assert!(lower <= upper);
 
@@ -7,17 +55,90 @@ pub fn counting_sort(A: &mut [i32], lower: i32, upper: i32) {
         slots[(x - lower) as usize] += 1;
     }
     let mut i = 0;
-    for slot in 0..n_slots {
-        if slots[slot] == 0 {
-            continue;
-        }
-        for _ in 0..slots[slot] {
+    for (slot, &count) in slots.iter().enumerate() {
+        for _ in 0..count {
             A[i] = slot as i32 + lower;
             i += 1;
         }
     }
 }
 
+// Turns the number of elements with every key into the first output
+// position of that key.
+pub(crate) fn counts_to_offsets(C: &mut [usize]) {
+    let mut total = 0;
+    for c in C.iter_mut() {
+        let count = *c;
+        *c = total;
+        total += count;
+    }
+}
+
+// Moves every element of `input` to the next free position of its key in
+// `output`, `C` holding the first position of every key.  Elements with
+// equal keys keep their relative order.
+pub(crate) fn distribute<T, F>(
+    input: &[T],
+    output: &mut [T],
+    C: &mut [usize],
+    key: F,
+) where
+    T: Clone,
+    F: Fn(&T) -> usize,
+{
+    for x in input {
+        let j = key(x);
+        output[C[j]] = x.clone();
+        C[j] += 1;
+    }
+}
+
+// The stable COUNTING-SORT of CLRS: sorts `input` by `key`, which must map
+// every element into `0..=k`, in O(n + k) time.  Elements with equal keys
+// keep their relative order.
+pub fn counting_sort_by_key<T, F>(input: &[T], k: usize, key: F) -> Vec<T>
+where
+    T: Clone,
+    F: Fn(&T) -> usize,
+{
+    let mut C = vec![0usize; k + 1];
+    for x in input {
+        C[key(x)] += 1;
+    }
+    // C[i] now contains the number of elements equal to i.
+    counts_to_offsets(&mut C);
+    // C[i] now contains the number of elements less than i.
+    let mut output = input.to_vec();
+    distribute(input, &mut output, &mut C, key);
+    output
+}
+
+// Key ranges up to this many slots per element, or up to `MIN_SLOTS`, are
+// counted; wider ones would cost more than sorting by comparisons.
+const SLOTS_PER_ELEMENT: u64 = 16;
+const MIN_SLOTS: u64 = 1 << 16;
+
+// Counting sort for any primitive integer, scanning the input for its bounds
+// first.  Takes O(n + k) time and space, `k` being `max - min`, but falls
+// back to merge sort when `k` is much larger than `n`.
+pub fn counting_sort_auto<T: CountingKey>(A: &mut [T]) {
+    if A.len() <= 1 {
+        return;
+    }
+
+    let min = *A.iter().min().unwrap();
+    let max = *A.iter().max().unwrap();
+    let k = max.offset_from(min);
+    if k > (A.len() as u64).saturating_mul(SLOTS_PER_ELEMENT).max(MIN_SLOTS) {
+        merge_sort(A);
+        return;
+    }
+
+    let sorted =
+        counting_sort_by_key(A, k as usize, |x| x.offset_from(min) as usize);
+    A.copy_from_slice(&sorted);
+}
+
 mod tests {
     #[test]
     fn counting_sort() {
@@ -28,4 +149,45 @@ mod tests {
         common::assert_asc(&A);
     }
 
+    #[test]
+    fn counting_sort_by_key() {
+        use super::counting_sort_by_key;
+        use crate::common;
+        let A = common::random_records(500, 20);
+        let B = counting_sort_by_key(&A, 19, |r| r.0 as usize);
+        assert_eq!(B.len(), A.len());
+        common::assert_stable(&B);
+    }
+
+    #[test]
+    fn counting_sort_auto() {
+        use super::counting_sort_auto;
+        use crate::common;
+
+        let mut A = common::random_vec::<i8>(300);
+        counting_sort_auto(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec_range::<u16>(300, 100, 2000);
+        counting_sort_auto(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec_range::<i64>(300, -1000, 1000);
+        counting_sort_auto(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        // A narrow range at the edges of a wide type.
+        let mut A = vec![i64::MAX, i64::MAX - 2, i64::MAX - 1];
+        counting_sort_auto(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A = vec![3u64, 0, 1];
+        counting_sort_auto(A.as_mut_slice());
+        assert_eq!(A, vec![0, 1, 3]);
+
+        // Far too wide a range to count.
+        let mut A = vec![i32::MAX, 0, i32::MIN, -1, i32::MAX];
+        counting_sort_auto(A.as_mut_slice());
+        assert_eq!(A, vec![i32::MIN, -1, 0, i32::MAX, i32::MAX]);
+    }
 }
@@ -1,3 +1,4 @@
 pub mod bucket_sort;
 pub mod counting_sort;
+pub mod msd_radix_sort;
 pub mod radix_sort;
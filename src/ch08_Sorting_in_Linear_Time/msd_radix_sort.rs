@@ -0,0 +1,334 @@
+use crate::ch02_Getting_Start::insertion_sort::{
+    insertion_sort_by_swap_impl,
+    insertion_sort_impl,
+};
+use std::mem;
+
+const INSERTION_SORT_THRESHOLD: usize = 16;
+
+// Bucket 0 holds the keys that end before `depth`, ahead of every byte.
+const N_BUCKETS: usize = 257;
+
+#[inline]
+fn bucket<T: AsRef<[u8]>>(x: &T, depth: usize) -> usize {
+    match x.as_ref().get(depth) {
+        Some(&byte) => byte as usize + 1,
+        None => 0,
+    }
+}
+
+// Compares the keys from `depth` on, the bytes before it being equal.
+fn suffix_less<T: AsRef<[u8]>>(depth: usize) -> impl FnMut(&T, &T) -> bool {
+    move |a, b| a.as_ref()[depth..] < b.as_ref()[depth..]
+}
+
+// Counters for every bucket at every level of recursion, allocated as the
+// recursion first reaches that level and reused by all later calls there.
+struct Counts {
+    levels: Vec<Vec<usize>>,
+    len: usize,
+}
+
+impl Counts {
+    fn new(len: usize) -> Counts {
+        Counts {
+            levels: Vec::new(),
+            len,
+        }
+    }
+
+    // The cleared counters of `level`, to be handed back with `put`.
+    fn take(&mut self, level: usize) -> Vec<usize> {
+        while self.levels.len() <= level {
+            self.levels.push(vec![0; self.len]);
+        }
+        let mut C = mem::take(&mut self.levels[level]);
+        for c in C.iter_mut() {
+            *c = 0;
+        }
+        C
+    }
+
+    fn put(&mut self, level: usize, C: Vec<usize>) {
+        self.levels[level] = C;
+    }
+}
+
+// The bucket in `1..N_BUCKETS` holding the most keys, `bounds` giving the
+// range of every bucket.
+fn largest_bucket<F>(bounds: F) -> usize
+where
+    F: Fn(usize) -> (usize, usize),
+{
+    (1..N_BUCKETS)
+        .max_by_key(|&b| {
+            let (start, end) = bounds(b);
+            end - start
+        })
+        .unwrap()
+}
+
+// Sorts the indices `idx` of elements of `A` rather than the elements
+// themselves, so that every level moves plain integers.  Recurses into
+// every bucket but the largest and loops on that one, so that each call at
+// `level` gets at most half the keys of the one before: the recursion stays
+// O(lg n) deep however long the keys share a prefix.
+fn msd_radix_sort_rec<T>(
+    A: &[T],
+    mut idx: &mut [usize],
+    mut aux: &mut [usize],
+    counts: &mut Counts,
+    level: usize,
+    mut depth: usize,
+) where
+    T: AsRef<[u8]>,
+{
+    while idx.len() > INSERTION_SORT_THRESHOLD {
+        // C[b] ends up as the first position of bucket `b`.
+        let mut C = counts.take(level);
+        for &i in idx.iter() {
+            C[bucket(&A[i], depth) + 1] += 1;
+        }
+        for b in 1..=N_BUCKETS {
+            C[b] += C[b - 1];
+        }
+
+        // Every C[b] moves on to the first position of bucket `b + 1`.
+        for &i in idx.iter() {
+            let b = bucket(&A[i], depth);
+            aux[C[b]] = i;
+            C[b] += 1;
+        }
+        idx.copy_from_slice(aux);
+
+        // The keys in bucket 0 are all equal.
+        let largest = largest_bucket(|b| (C[b - 1], C[b]));
+        for b in (1..N_BUCKETS).filter(|&b| b != largest) {
+            let (start, end) = (C[b - 1], C[b]);
+            if end - start > 1 {
+                msd_radix_sort_rec(
+                    A,
+                    &mut idx[start..end],
+                    &mut aux[start..end],
+                    counts,
+                    level + 1,
+                    depth + 1,
+                );
+            }
+        }
+
+        let (start, end) = (C[largest - 1], C[largest]);
+        counts.put(level, C);
+        idx = &mut mem::take(&mut idx)[start..end];
+        aux = &mut mem::take(&mut aux)[start..end];
+        depth += 1;
+    }
+
+    let mut less = suffix_less(depth);
+    insertion_sort_impl(idx, &mut |&i: &usize, &j: &usize| less(&A[i], &A[j]));
+}
+
+// Moves `A[idx[k]]` to position `k` for every `k`, following the cycles of
+// the permutation.  Leaves `idx` as the identity.
+fn apply_permutation<T>(A: &mut [T], idx: &mut [usize]) {
+    for i in 0..A.len() {
+        let mut cur = i;
+        loop {
+            let next = idx[cur];
+            idx[cur] = cur;
+            if next == i {
+                break;
+            }
+            A.swap(cur, next);
+            cur = next;
+        }
+    }
+}
+
+// Sorts byte strings lexicographically, distributing them into 256 buckets by
+// their first byte and recursing on every bucket with the next byte.  Takes
+// O(n + N) time for `N` bytes examined, and O(n) extra space for the indices
+// of the elements, which are only moved once their order is known.
+pub fn msd_radix_sort<T>(A: &mut [T])
+where
+    T: AsRef<[u8]>,
+{
+    if A.len() <= 1 {
+        return;
+    }
+    let mut idx: Vec<usize> = (0..A.len()).collect();
+    let mut aux = vec![0; A.len()];
+    msd_radix_sort_rec(
+        A,
+        &mut idx,
+        &mut aux,
+        &mut Counts::new(N_BUCKETS + 1),
+        0,
+        0,
+    );
+    apply_permutation(A, &mut idx);
+}
+
+// Loops on the largest bucket like `msd_radix_sort_rec`.
+fn american_flag_sort_rec<T>(
+    mut A: &mut [T],
+    counts: &mut Counts,
+    level: usize,
+    mut depth: usize,
+) where
+    T: AsRef<[u8]>,
+{
+    while A.len() > INSERTION_SORT_THRESHOLD {
+        // The first half of the counters ends up holding the first position
+        // of every bucket, the second half the next free slot of every
+        // bucket.
+        let mut counters = counts.take(level);
+        let (C, next) = counters.split_at_mut(N_BUCKETS + 1);
+        for x in A.iter() {
+            C[bucket(x, depth) + 1] += 1;
+        }
+        for b in 1..=N_BUCKETS {
+            C[b] += C[b - 1];
+        }
+
+        // Permute in place: swap every misplaced key straight into the next
+        // free slot of its bucket, until each bucket holds only its own keys.
+        next.copy_from_slice(C);
+        for b in 0..N_BUCKETS {
+            while next[b] < C[b + 1] {
+                let d = bucket(&A[next[b]], depth);
+                if d != b {
+                    A.swap(next[b], next[d]);
+                }
+                next[d] += 1;
+            }
+        }
+
+        let largest = largest_bucket(|b| (C[b], C[b + 1]));
+        for b in (1..N_BUCKETS).filter(|&b| b != largest) {
+            let (start, end) = (C[b], C[b + 1]);
+            if end - start > 1 {
+                american_flag_sort_rec(
+                    &mut A[start..end],
+                    counts,
+                    level + 1,
+                    depth + 1,
+                );
+            }
+        }
+
+        let (start, end) = (C[largest], C[largest + 1]);
+        counts.put(level, counters);
+        A = &mut mem::take(&mut A)[start..end];
+        depth += 1;
+    }
+
+    insertion_sort_by_swap_impl(A, &mut suffix_less(depth));
+}
+
+// MSD radix sort that permutes the keys in place instead of distributing them
+// into an auxiliary array (McIlroy, Bostic and McIlroy's "American flag"
+// sort).  Not stable.
+pub fn american_flag_sort<T>(A: &mut [T])
+where
+    T: AsRef<[u8]>,
+{
+    american_flag_sort_rec(A, &mut Counts::new(2 * (N_BUCKETS + 1)), 0, 0);
+}
+
+mod tests {
+    fn random_strings(n: usize) -> Vec<String> {
+        use crate::common;
+        // Short keys over a small alphabet, to share plenty of prefixes.
+        common::random_vec_range::<u8>(n, 0, 12)
+            .into_iter()
+            .zip(common::random_vec::<u64>(n))
+            .map(|(len, x)| {
+                let s = format!("{:o}", x);
+                s[..(len as usize).min(s.len())].to_string()
+            })
+            .collect()
+    }
+
+    // Keys that agree on their first 100 KB, which a sort recursing once per
+    // byte would need as many stack frames for.
+    fn shared_prefix_strings(n: usize) -> Vec<String> {
+        let prefix = "x".repeat(100_000);
+        random_strings(n)
+            .into_iter()
+            .map(|s| format!("{}{}", prefix, s))
+            .collect()
+    }
+
+    #[test]
+    fn msd_radix_sort() {
+        use super::msd_radix_sort;
+        for &n in &[0, 1, 2, 16, 17, 1000, 5000] {
+            let mut A = random_strings(n);
+            let mut B = A.clone();
+            msd_radix_sort(A.as_mut_slice());
+            B.sort();
+            assert_eq!(A, B);
+        }
+
+        let mut A = vec!["héllo", "hello", "", "h", "hellø", "he", "ħ"];
+        msd_radix_sort(A.as_mut_slice());
+        assert_eq!(A, vec!["", "h", "he", "hello", "hellø", "héllo", "ħ"]);
+
+        let mut A = shared_prefix_strings(40);
+        let mut B = A.clone();
+        msd_radix_sort(A.as_mut_slice());
+        B.sort();
+        assert_eq!(A, B);
+    }
+
+    #[test]
+    fn msd_radix_sort_stable() {
+        use super::msd_radix_sort;
+
+        // Equal keys, told apart by their original position.
+        #[derive(Clone, Debug, PartialEq)]
+        struct Record(String, usize);
+        impl AsRef<[u8]> for Record {
+            fn as_ref(&self) -> &[u8] {
+                self.0.as_ref()
+            }
+        }
+
+        let mut A: Vec<Record> = random_strings(5000)
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| Record(s[..s.len().min(2)].to_string(), i))
+            .collect();
+        let mut B = A.clone();
+        msd_radix_sort(A.as_mut_slice());
+        B.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(A, B);
+    }
+
+    #[test]
+    fn american_flag_sort() {
+        use super::american_flag_sort;
+        for &n in &[0, 1, 2, 16, 17, 1000, 5000] {
+            let mut A = random_strings(n);
+            let mut B = A.clone();
+            american_flag_sort(A.as_mut_slice());
+            B.sort();
+            assert_eq!(A, B);
+        }
+
+        let mut A: Vec<Vec<u8>> = (0..300u32)
+            .map(|x| (x * 7919 % 1000).to_be_bytes().to_vec())
+            .collect();
+        let mut B = A.clone();
+        american_flag_sort(A.as_mut_slice());
+        B.sort();
+        assert_eq!(A, B);
+
+        let mut A = shared_prefix_strings(40);
+        let mut B = A.clone();
+        american_flag_sort(A.as_mut_slice());
+        B.sort();
+        assert_eq!(A, B);
+    }
+}
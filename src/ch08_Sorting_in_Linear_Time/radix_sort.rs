@@ -1,76 +1,50 @@
-use std::mem;
-
 mod traits {
-    use num::{
-        FromPrimitive,
-        PrimInt,
-        ToPrimitive,
-    };
     use std::mem;
 
+    // Keys that split into fixed-width digits, ordered so that comparing the
+    // digits from the most significant down sorts the keys.
     pub trait Radix: Clone {
-        fn n_buckets() -> usize;
-        fn n_rounds() -> usize;
-        fn get_bucket(&self, round: usize) -> usize;
+        fn n_bits() -> u32;
+        // The `width` bits starting at bit `shift`, which is below `n_bits`.
+        // Bits past `n_bits` read as zero.
+        fn digit(&self, shift: u32, width: u32) -> usize;
     }
 
     #[inline]
-    fn nth_hex_digit<T>(x: T, n: u32) -> u8
-    where
-        T: FromPrimitive + PrimInt + ToPrimitive,
-    {
-        // `n` start from 0
-        let mask = T::from_u8(0xfu8).unwrap();
-        x.unsigned_shr(n * 4).bitand(mask).to_u8().unwrap()
+    fn nth_digit(x: u64, shift: u32, width: u32) -> usize {
+        ((x >> shift) & ((1 << width) - 1)) as usize
     }
 
     macro_rules! impl_radix_unsigned {
         ($t:ty) => {
             impl Radix for $t {
                 #[inline]
-                fn n_buckets() -> usize {
-                    0x10
+                fn n_bits() -> u32 {
+                    mem::size_of::<$t>() as u32 * 8
                 }
 
                 #[inline]
-                fn n_rounds() -> usize {
-                    mem::size_of::<$t>() * 2
-                }
-
-                #[inline]
-                fn get_bucket(&self, round: usize) -> usize {
-                    nth_hex_digit(*self, round as u32) as usize
+                fn digit(&self, shift: u32, width: u32) -> usize {
+                    nth_digit(*self as u64, shift, width)
                 }
             }
         };
     }
 
+    // Flipping the sign bit maps the signed range onto the unsigned one in
+    // order.
     macro_rules! impl_radix_signed {
-        ($t:ty) => {
+        ($t:ty, $u:ty) => {
             impl Radix for $t {
                 #[inline]
-                fn n_buckets() -> usize {
-                    0x10
+                fn n_bits() -> u32 {
+                    mem::size_of::<$t>() as u32 * 8
                 }
 
                 #[inline]
-                fn n_rounds() -> usize {
-                    mem::size_of::<$t>() * 2 + 1
-                }
-
-                #[inline]
-                fn get_bucket(&self, round: usize) -> usize {
-                    if round < <$t as Radix>::n_rounds() - 1 {
-                        return nth_hex_digit(*self, round as u32) as usize;
-                    }
-
-                    if *self < 0 {
-                        0
-                    } else if *self == 0 {
-                        1
-                    } else {
-                        2
-                    }
+                fn digit(&self, shift: u32, width: u32) -> usize {
+                    let sign = 1 << (<$t as Radix>::n_bits() - 1);
+                    nth_digit((*self as $u as u64) ^ sign, shift, width)
                 }
             }
         };
@@ -82,68 +56,213 @@ mod traits {
     impl_radix_unsigned!(u64);
     impl_radix_unsigned!(usize);
 
-    impl_radix_signed!(i8);
-    impl_radix_signed!(i16);
-    impl_radix_signed!(i32);
-    impl_radix_signed!(i64);
-    impl_radix_signed!(isize);
-}
+    impl_radix_signed!(i8, u8);
+    impl_radix_signed!(i16, u16);
+    impl_radix_signed!(i32, u32);
+    impl_radix_signed!(i64, u64);
+    impl_radix_signed!(isize, usize);
 
-use traits::*;
+    // Negative floats have all their bits flipped, reversing their order,
+    // and positive ones only the sign bit, moving them above the negatives.
+    // This orders the keys as IEEE 754 `totalOrder`: -NaN < -inf < ... < -0.0
+    // < 0.0 < ... < inf < NaN.
+    macro_rules! impl_radix_float {
+        ($t:ty, $u:ty) => {
+            impl Radix for $t {
+                #[inline]
+                fn n_bits() -> u32 {
+                    mem::size_of::<$t>() as u32 * 8
+                }
 
-const BUCKET_NUM: usize = 0x10;
+                #[inline]
+                fn digit(&self, shift: u32, width: u32) -> usize {
+                    let bits = self.to_bits();
+                    let sign = 1 << (<$t as Radix>::n_bits() - 1);
+                    let key = if bits & sign == 0 {
+                        bits ^ sign
+                    } else {
+                        !bits
+                    };
+                    nth_digit(key as u64, shift, width)
+                }
+            }
+        };
+    }
 
-#[inline]
-pub fn fill_bucket_from_iter<T, I>(
-    buckets: &mut Vec<Vec<T>>,
-    iter: I,
-    round: usize,
-) where
-    T: Radix,
-    I: Iterator<Item = T>,
-{
-    for x in iter {
-        buckets[x.get_bucket(round)].push(x);
+    impl_radix_float!(f32, u32);
+    impl_radix_float!(f64, u64);
+
+    // The digit of a key made of a `high` part above a `low` part with
+    // `n_low_bits` bits, which may take bits from both.
+    #[inline]
+    fn split_digit<H, L>(
+        high: H,
+        low: L,
+        n_low_bits: u32,
+        shift: u32,
+        width: u32,
+    ) -> usize
+    where
+        H: Fn(u32, u32) -> usize,
+        L: Fn(u32, u32) -> usize,
+    {
+        if shift >= n_low_bits {
+            return high(shift - n_low_bits, width);
+        }
+        let low_width = n_low_bits - shift;
+        if width <= low_width {
+            return low(shift, width);
+        }
+        low(shift, low_width) | high(0, width - low_width) << low_width
+    }
+
+    // Tuples sort lexicographically, the first field being the most
+    // significant.
+    impl<A: Radix, B: Radix> Radix for (A, B) {
+        #[inline]
+        fn n_bits() -> u32 {
+            A::n_bits() + B::n_bits()
+        }
+
+        #[inline]
+        fn digit(&self, shift: u32, width: u32) -> usize {
+            split_digit(
+                |s, w| self.0.digit(s, w),
+                |s, w| self.1.digit(s, w),
+                B::n_bits(),
+                shift,
+                width,
+            )
+        }
+    }
+
+    impl<A: Radix, B: Radix, C: Radix> Radix for (A, B, C) {
+        #[inline]
+        fn n_bits() -> u32 {
+            A::n_bits() + B::n_bits() + C::n_bits()
+        }
+
+        #[inline]
+        fn digit(&self, shift: u32, width: u32) -> usize {
+            split_digit(
+                |s, w| self.0.digit(s, w),
+                |s, w| {
+                    split_digit(
+                        |s, w| self.1.digit(s, w),
+                        |s, w| self.2.digit(s, w),
+                        C::n_bits(),
+                        s,
+                        w,
+                    )
+                },
+                B::n_bits() + C::n_bits(),
+                shift,
+                width,
+            )
+        }
+    }
+
+    // Byte arrays sort lexicographically, as big-endian numbers.
+    impl<const N: usize> Radix for [u8; N] {
+        #[inline]
+        fn n_bits() -> u32 {
+            N as u32 * 8
+        }
+
+        fn digit(&self, shift: u32, width: u32) -> usize {
+            let mut digit = 0;
+            let mut taken = 0;
+            while taken < width && shift + taken < Self::n_bits() {
+                let bit = shift + taken;
+                let byte = self[N - 1 - (bit / 8) as usize];
+                let n = (8 - bit % 8).min(width - taken);
+                digit |= nth_digit(byte as u64, bit % 8, n) << taken;
+                taken += n;
+            }
+            digit
+        }
     }
 }
 
-pub fn radix_sort<T>(A: &mut [T])
+pub use traits::Radix;
+
+use super::counting_sort::{
+    counts_to_offsets,
+    distribute,
+};
+
+pub const DEFAULT_RADIX_WIDTH: u32 = 8;
+
+// Returns the number of passes run.
+fn radix_sort_with_width_impl<T>(A: &mut [T], width: u32) -> usize
 where
     T: Radix,
 {
-    let n_rounds = T::n_rounds();
-    let n_buckets = T::n_buckets();
-    let bucket_preset_capacity = A.len() / n_buckets;
+    assert!(
+        (1..=16).contains(&width),
+        "digit width must be between 1 and 16 bits"
+    );
+    if A.len() <= 1 {
+        return 0;
+    }
 
-    let mut buckets_A: Vec<Vec<T>> = Vec::with_capacity(n_buckets);
-    let mut buckets_B: Vec<Vec<T>> = Vec::with_capacity(n_buckets);
+    let n_buckets = 1 << width;
+    let n_passes = T::n_bits().div_ceil(width) as usize;
 
-    for _ in 0..n_buckets {
-        buckets_A.push(Vec::with_capacity(bucket_preset_capacity));
-        buckets_B.push(Vec::with_capacity(bucket_preset_capacity));
+    // A single scan counts the digits of every pass.
+    let mut counts = vec![0usize; n_passes * n_buckets];
+    for x in A.iter() {
+        for pass in 0..n_passes {
+            counts[pass * n_buckets + x.digit(pass as u32 * width, width)] += 1;
+        }
     }
 
-    fill_bucket_from_iter(&mut buckets_A, A.iter().cloned(), 0);
-
-    for round in 1..n_rounds {
-        for bucket in &mut buckets_B {
-            bucket.clear()
+    let mut B = Vec::new();
+    let mut sorted_in_A = true;
+    let mut passes_run = 0;
+    for (pass, C) in counts.chunks_mut(n_buckets).enumerate() {
+        if C.contains(&A.len()) {
+            continue;
         }
 
-        for bucket in &buckets_A {
-            fill_bucket_from_iter(&mut buckets_B, bucket.iter().cloned(), round)
+        // One LSD pass: a stable counting sort by the digit at `shift`.
+        counts_to_offsets(C);
+        let shift = pass as u32 * width;
+        let digit = |x: &T| x.digit(shift, width);
+        if sorted_in_A {
+            if B.is_empty() {
+                B = A.to_vec();
+            }
+            distribute(A, &mut B, C, digit);
+        } else {
+            distribute(&B, A, C, digit);
         }
+        sorted_in_A = !sorted_in_A;
+        passes_run += 1;
+    }
 
-        mem::swap(&mut buckets_A, &mut buckets_B);
+    if !sorted_in_A {
+        A.clone_from_slice(&B);
     }
+    passes_run
+}
 
-    let mut i = 0;
-    buckets_A.into_iter().for_each(|bucket| {
-        bucket.into_iter().for_each(|x| {
-            A[i] = x;
-            i += 1;
-        })
-    });
+// Sorts with digits of `width` bits, taking ceil(b / width) passes over
+// `b`-bit keys.  Wider digits mean fewer passes but `2^width` counters per
+// pass; 8 or 11 bits suit large inputs.  Passes where every key shares the
+// same digit are skipped.
+pub fn radix_sort_with_width<T>(A: &mut [T], width: u32)
+where
+    T: Radix,
+{
+    radix_sort_with_width_impl(A, width);
+}
+
+pub fn radix_sort<T>(A: &mut [T])
+where
+    T: Radix,
+{
+    radix_sort_with_width(A, DEFAULT_RADIX_WIDTH);
 }
 
 mod tests {
@@ -178,4 +297,117 @@ mod tests {
         test_radix_sort!(usize);
     }
 
+    macro_rules! test_radix_sort_with_width {
+        ($t:ty, $width:expr) => {
+            let mut A = common::random_vec_range::<$t>(
+                500,
+                Bounded::min_value(),
+                Bounded::max_value(),
+            );
+            radix_sort_with_width(A.as_mut_slice(), $width);
+            common::assert_asc(&A);
+        };
+    }
+
+    #[test]
+    fn radix_sort_with_width() {
+        use super::radix_sort_with_width;
+        use crate::common;
+        use num::Bounded;
+
+        for &width in &[1, 4, 8, 11, 16] {
+            test_radix_sort_with_width!(i8, width);
+            test_radix_sort_with_width!(i16, width);
+            test_radix_sort_with_width!(i32, width);
+            test_radix_sort_with_width!(i64, width);
+            test_radix_sort_with_width!(u8, width);
+            test_radix_sort_with_width!(u32, width);
+            test_radix_sort_with_width!(u64, width);
+        }
+    }
+
+    #[test]
+    fn radix_sort_skipped_passes() {
+        use super::radix_sort_with_width_impl;
+        use crate::common;
+
+        // Only the lowest digit differs, so a single pass does the work.
+        let mut A =
+            common::random_vec_range::<u64>(500, 1 << 40, (1 << 40) + 200);
+        A.extend(vec![1 << 40, (1 << 40) + 199]);
+        assert_eq!(radix_sort_with_width_impl(A.as_mut_slice(), 8), 1);
+        common::assert_asc(&A);
+
+        let mut A = vec![-7i32; 10];
+        assert_eq!(radix_sort_with_width_impl(A.as_mut_slice(), 11), 0);
+        assert_eq!(A, vec![-7; 10]);
+
+        let mut A = vec![3i64, -1, 3, i64::MIN, i64::MAX, 0, -1];
+        assert_eq!(radix_sort_with_width_impl(A.as_mut_slice(), 16), 4);
+        assert_eq!(A, vec![i64::MIN, -1, -1, 0, 3, 3, i64::MAX]);
+    }
+
+    #[test]
+    fn radix_sort_floats() {
+        use super::radix_sort;
+        use crate::common;
+
+        let mut A = common::random_vec::<f64>(300);
+        A.extend(A.clone().iter().map(|x| -x * 1e10));
+        A.extend(vec![0.0, -0.0, f64::INFINITY, f64::NEG_INFINITY]);
+        radix_sort(A.as_mut_slice());
+        common::assert_asc(&A);
+
+        let mut A = vec![1.5f32, -0.0, -2.25, 0.0, 1e-40, -1e30, 3.0];
+        radix_sort(A.as_mut_slice());
+        assert_eq!(A, vec![-1e30, -2.25, -0.0, 0.0, 1e-40, 1.5, 3.0]);
+        assert!(A[2].is_sign_negative() && A[3].is_sign_positive());
+    }
+
+    #[test]
+    fn radix_sort_tuples() {
+        use super::radix_sort_with_width;
+        use crate::common;
+
+        for &width in &[4, 8, 11, 16] {
+            let mut A: Vec<(i8, u16)> = common::random_vec::<i8>(300)
+                .into_iter()
+                .zip(common::random_vec::<u16>(300))
+                .map(|(a, b)| (a % 4, b))
+                .collect();
+            let mut B = A.clone();
+            radix_sort_with_width(A.as_mut_slice(), width);
+            B.sort();
+            assert_eq!(A, B);
+
+            let mut A: Vec<(u8, i32, u8)> = common::random_vec::<i32>(300)
+                .into_iter()
+                .map(|x| ((x & 3) as u8, x % 10, (x >> 8) as u8))
+                .collect();
+            let mut B = A.clone();
+            radix_sort_with_width(A.as_mut_slice(), width);
+            B.sort();
+            assert_eq!(A, B);
+        }
+    }
+
+    #[test]
+    fn radix_sort_byte_arrays() {
+        use super::radix_sort_with_width;
+        use crate::common;
+
+        for &width in &[4, 8, 11, 16] {
+            let mut A: Vec<[u8; 5]> = common::random_vec::<u64>(300)
+                .into_iter()
+                .map(|x| {
+                    let b = x.to_be_bytes();
+                    [b[0] % 3, b[1], b[2], b[3] % 2, b[4]]
+                })
+                .collect();
+            let mut B = A.clone();
+            radix_sort_with_width(A.as_mut_slice(), width);
+            B.sort();
+            assert_eq!(A, B);
+        }
+    }
 }
@@ -1,23 +1,61 @@
-use crate::ch07_Quicksort::randomized_partition;
-use std::cmp::PartialOrd;
+use crate::{
+    ch07_Quicksort::randomized_partition3_impl,
+    common::compare,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
 
-pub fn randomized_select<T: PartialOrd + Copy>(A: &mut [T], i: usize) -> T {
+// Rearranges `A` so that `A[i]` is the `i`-th smallest element (counting
+// from 0), no element before it being greater and none after it being less.
+pub(crate) fn randomized_select_impl<T, F>(
+    A: &mut [T],
+    i: usize,
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
     assert!(i < A.len());
 
     if A.len() == 1 {
-        return A[0];
+        return;
     }
 
-    let q = randomized_partition(A);
-    if q == i {
-        A[q]
-    } else if q < i {
-        randomized_select(&mut A[q..], i - q)
-    } else {
-        randomized_select(&mut A[..q], i)
+    // Three-way partitioning steps over the whole run of keys equal to the
+    // pivot at once, so inputs with few distinct keys stay linear.
+    let (lt, gt) = randomized_partition3_impl(A, is_less);
+    if i < lt {
+        randomized_select_impl(&mut A[..lt], i, is_less)
+    } else if i >= gt {
+        randomized_select_impl(&mut A[gt..], i - gt, is_less)
     }
 }
 
+// The `i`-th smallest element of `A`, in O(n) expected time.  Leaves `A`
+// partitioned around it, at index `i`.
+pub fn randomized_select<T: PartialOrd>(A: &mut [T], i: usize) -> &T {
+    randomized_select_impl(A, i, &mut compare::by_partial_ord());
+    &A[i]
+}
+
+pub fn randomized_select_by<T, F>(A: &mut [T], i: usize, compare: F) -> &T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    randomized_select_impl(A, i, &mut compare::by_ordering(compare));
+    &A[i]
+}
+
+pub fn randomized_select_by_key<T, K, F>(A: &mut [T], i: usize, f: F) -> &T
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    randomized_select_impl(A, i, &mut compare::by_key(f));
+    &A[i]
+}
+
 mod tests {
     #[test]
     fn randomized_select() {
@@ -28,9 +66,44 @@ mod tests {
         let mut A = common::random_vec::<i64>(100);
         let mut rng = rand::thread_rng();
         let i = rng.gen_range(0, 100);
-        let x = randomized_select(A.as_mut_slice(), i);
+        let x = *randomized_select(A.as_mut_slice(), i);
+        assert!(A[..i].iter().all(|&y| y <= x));
+        assert!(A[i..].iter().all(|&y| y >= x));
         A.sort();
         assert_eq!(x, A[i]);
     }
 
+    #[test]
+    fn randomized_select_duplicates() {
+        use super::randomized_select;
+        use crate::common;
+        let mut A = common::random_vec_range::<u8>(100_000, 0, 3);
+        let mut sorted = A.clone();
+        sorted.sort();
+        for &i in &[0, 33_333, 50_000, 99_999] {
+            assert_eq!(*randomized_select(A.as_mut_slice(), i), sorted[i]);
+        }
+    }
+
+    #[test]
+    fn randomized_select_strings() {
+        use super::{
+            randomized_select,
+            randomized_select_by,
+            randomized_select_by_key,
+        };
+        let words =
+            ["pear", "fig", "apple", "kiwi", "banana", "cherry", "date"];
+        let mut A: Vec<String> = words.iter().map(|s| s.to_string()).collect();
+        assert_eq!(randomized_select(A.as_mut_slice(), 0), "apple");
+        assert_eq!(randomized_select(A.as_mut_slice(), 3), "date");
+        assert_eq!(
+            randomized_select_by(A.as_mut_slice(), 1, |a, b| b.cmp(a)),
+            "kiwi"
+        );
+        assert_eq!(
+            randomized_select_by_key(A.as_mut_slice(), 6, |s| s.len()).len(),
+            6
+        );
+    }
 }
@@ -1,44 +1,72 @@
 use crate::{
-    ch02_Getting_Start::insertion_sort::insertion_sort,
-    ch07_Quicksort::partition_by,
+    ch02_Getting_Start::insertion_sort::insertion_sort_by_swap_impl,
+    ch07_Quicksort::partition3_by_impl,
+    common::compare,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
 };
-use std::cmp::PartialOrd;
-use std::f64;
 
-pub fn select<T: PartialOrd + Copy>(A: &mut [T], i: usize) -> T {
+// Rearranges `A` so that `A[i]` is the `i`-th smallest element (counting
+// from 0), no element before it being greater and none after it being less.
+pub(crate) fn select_impl<T, F>(A: &mut [T], i: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
     assert!(i < A.len());
 
-    if A.len() == 1 {
-        return A[0];
+    if A.len() <= 5 {
+        insertion_sort_by_swap_impl(A, is_less);
+        return;
+    }
+
+    // Sort every group of 5 and gather the medians at the front of `A`.
+    // Group `g` starts at `5g`, past the slot `g` receiving its median, so
+    // only groups already done get disturbed.
+    let n_groups = A.len().div_ceil(5);
+    for g in 0..n_groups {
+        let start = 5 * g;
+        let end = A.len().min(start + 5);
+        insertion_sort_by_swap_impl(&mut A[start..end], is_less);
+        A.swap(g, (start + end) / 2);
     }
 
-    let mut medians = {
-        let mut medians =
-            Vec::with_capacity((A.len() as f64 / 5f64).ceil() as usize);
+    // The median of medians lands at index `n_groups / 2`.
+    select_impl(&mut A[..n_groups], n_groups / 2, is_less);
+    let (lt, gt) = partition3_by_impl(A, n_groups / 2, is_less);
 
-        for chunk in A.chunks_mut(5) {
-            insertion_sort(chunk);
-            medians.push(chunk[chunk.len() / 2]);
-        }
-        medians
-    };
-
-    let m_of_m_index = {
-        let median_index = medians.len() / 2;
-        let m_of_m = select(&mut medians, median_index);
-        A.iter().position(|&r| r == m_of_m).unwrap()
-    };
-    let q = partition_by(A, m_of_m_index);
-
-    if q == i {
-        A[q]
-    } else if q < i {
-        select(&mut A[q..], i - q)
-    } else {
-        select(&mut A[..q], i)
+    if i < lt {
+        select_impl(&mut A[..lt], i, is_less)
+    } else if i >= gt {
+        select_impl(&mut A[gt..], i - gt, is_less)
     }
 }
 
+// The `i`-th smallest element of `A`, in O(n) worst-case time.  Leaves `A`
+// partitioned around it, at index `i`.
+pub fn select<T: PartialOrd>(A: &mut [T], i: usize) -> &T {
+    select_impl(A, i, &mut compare::by_partial_ord());
+    &A[i]
+}
+
+pub fn select_by<T, F>(A: &mut [T], i: usize, compare: F) -> &T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    select_impl(A, i, &mut compare::by_ordering(compare));
+    &A[i]
+}
+
+pub fn select_by_key<T, K, F>(A: &mut [T], i: usize, f: F) -> &T
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    select_impl(A, i, &mut compare::by_key(f));
+    &A[i]
+}
+
 mod tests {
     #[test]
     fn select() {
@@ -49,9 +77,64 @@ mod tests {
         let mut A = common::random_vec::<i64>(100);
         let mut rng = rand::thread_rng();
         let i = rng.gen_range(0, 100);
-        let x = select(A.as_mut_slice(), i);
+        let x = *select(A.as_mut_slice(), i);
+        assert!(A[..i].iter().all(|&y| y <= x));
+        assert!(A[i..].iter().all(|&y| y >= x));
         A.sort();
         assert_eq!(x, A[i]);
     }
 
+    #[test]
+    fn select_every_rank() {
+        use super::select;
+        use crate::common;
+        for &n in &[1, 2, 5, 6, 11, 26, 137] {
+            let A = common::random_vec_range::<i32>(n, -20, 20);
+            let mut sorted = A.clone();
+            sorted.sort();
+            for (i, x) in sorted.iter().enumerate() {
+                let mut B = A.clone();
+                assert_eq!(select(B.as_mut_slice(), i), x);
+            }
+        }
+    }
+
+    #[test]
+    fn select_duplicates() {
+        use super::select;
+        use crate::common;
+        let mut A = common::random_vec_range::<u8>(10_000, 0, 3);
+        let mut sorted = A.clone();
+        sorted.sort();
+        for &i in &[0, 3_333, 5_000, 9_999] {
+            assert_eq!(*select(A.as_mut_slice(), i), sorted[i]);
+        }
+    }
+
+    #[test]
+    fn select_by_key() {
+        use super::{
+            select_by,
+            select_by_key,
+        };
+        use crate::common;
+
+        // Neither `Copy` nor `Clone`.
+        struct Record(u32, String);
+
+        let keys = common::random_vec_range::<u32>(500, 0, 1000);
+        let mut sorted = keys.clone();
+        sorted.sort();
+        let mut A: Vec<_> =
+            keys.iter().map(|&k| Record(k, k.to_string())).collect();
+        for &i in &[0, 123, 250, 499] {
+            assert_eq!(
+                select_by_key(A.as_mut_slice(), i, |r| r.0).0,
+                sorted[i]
+            );
+            let r = select_by(A.as_mut_slice(), i, |a, b| b.0.cmp(&a.0));
+            assert_eq!(r.0, sorted[499 - i]);
+            assert_eq!(r.1, sorted[499 - i].to_string());
+        }
+    }
 }
@@ -0,0 +1,13 @@
+pub mod par_merge_sort;
+pub mod par_quick_sort;
+
+use std::thread;
+
+// Subproblems at or below this many elements are solved sequentially.
+pub const DEFAULT_GRAIN_SIZE: usize = 4096;
+
+// Enough levels of forking to give every available core a thread.
+pub(crate) fn max_depth() -> usize {
+    let n = thread::available_parallelism().map_or(1, |n| n.get());
+    n.next_power_of_two().trailing_zeros() as usize
+}
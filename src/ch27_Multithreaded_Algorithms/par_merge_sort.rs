@@ -0,0 +1,207 @@
+use super::max_depth;
+use crate::ch02_Getting_Start::{
+    merge_sort::{
+        merge,
+        split_merge,
+    },
+    search::partition_point,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+use std::thread;
+
+// Number of elements in `A` that must be placed before `x`: those less
+// than `x` when `x` comes from the left run, or those not greater than `x`
+// when it comes from the right run.  Splitting this way keeps ties in their
+// original order.
+fn split_point<T, F>(A: &[T], x: &T, x_from_left: bool, is_less: &F) -> usize
+where
+    F: Fn(&T, &T) -> bool,
+{
+    if x_from_left {
+        partition_point(A, |y| is_less(y, x))
+    } else {
+        partition_point(A, |y| !is_less(x, y))
+    }
+}
+
+// P-MERGE from CLRS 27.3: the median of the longer run splits both runs
+// into two independent merges.  Like `par_merge_sort_impl`, forks at most
+// `depth` levels deep and returns the most threads it had running at once.
+fn p_merge<T, F>(
+    A: &[T],
+    B: &[T],
+    out: &mut [T],
+    grain: usize,
+    depth: usize,
+    is_less: &F,
+) -> usize
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    if out.len() <= grain || depth == 0 || A.is_empty() || B.is_empty() {
+        merge(A, B, out, &mut |a: &T, b: &T| is_less(a, b));
+        return 0;
+    }
+
+    // `x` is the median of the longer run; it lands right between both
+    // halves of the output.
+    let x_from_A = A.len() >= B.len();
+    let (q1, q2) = if x_from_A {
+        let q1 = A.len() / 2;
+        (q1, split_point(B, &A[q1], true, is_less))
+    } else {
+        let q2 = B.len() / 2;
+        (split_point(A, &B[q2], false, is_less), q2)
+    };
+
+    let (out_left, out_right) = out.split_at_mut(q1 + q2);
+    let (x, out_right) = out_right.split_first_mut().unwrap();
+    *x = if x_from_A {
+        A[q1].clone()
+    } else {
+        B[q2].clone()
+    };
+    let (A_left, A_right) = (&A[..q1], &A[q1 + x_from_A as usize..]);
+    let (B_left, B_right) = (&B[..q2], &B[q2 + !x_from_A as usize..]);
+
+    thread::scope(|s| {
+        let spawned = s.spawn(|| {
+            p_merge(A_left, B_left, out_left, grain, depth - 1, is_less)
+        });
+        let n_threads =
+            p_merge(A_right, B_right, out_right, grain, depth - 1, is_less);
+        1 + n_threads + spawned.join().unwrap()
+    })
+}
+
+// Sorts `src` into `dst`, both holding the same elements on entry, swapping
+// their roles at every level as `split_merge` does.  Forks the halves and
+// their merge onto threads at most `depth` levels deep, so at most
+// 2^depth - 1 threads run at once, and returns the most it had running.
+fn par_split_merge<T, F>(
+    src: &mut [T],
+    dst: &mut [T],
+    grain: usize,
+    depth: usize,
+    is_less: &F,
+) -> usize
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    if dst.len() <= grain || depth == 0 {
+        split_merge(src, dst, &mut |a: &T, b: &T| is_less(a, b));
+        return 0;
+    }
+
+    let mid = dst.len() / 2;
+    let (src_left, src_right) = src.split_at_mut(mid);
+    let (dst_left, dst_right) = dst.split_at_mut(mid);
+    let sort_threads = thread::scope(|s| {
+        let spawned = s.spawn(|| {
+            par_split_merge(dst_left, src_left, grain, depth - 1, is_less)
+        });
+        let n_threads =
+            par_split_merge(dst_right, src_right, grain, depth - 1, is_less);
+        1 + n_threads + spawned.join().unwrap()
+    });
+
+    let merge_threads =
+        p_merge(src_left, src_right, dst, grain, depth, is_less);
+    sort_threads.max(merge_threads)
+}
+
+fn par_merge_sort_impl<T, F>(
+    A: &mut [T],
+    grain: usize,
+    depth: usize,
+    is_less: &F,
+) -> usize
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    if A.len() <= 1 {
+        return 0;
+    }
+    let mut buffer = A.to_vec();
+    par_split_merge(&mut buffer, A, grain, depth, is_less)
+}
+
+pub fn par_merge_sort<T>(A: &mut [T], grain: usize)
+where
+    T: PartialOrd + Clone + Send + Sync,
+{
+    par_merge_sort_impl(A, grain, max_depth(), &|a: &T, b: &T| a < b);
+}
+
+pub fn par_merge_sort_by<T, F>(A: &mut [T], grain: usize, compare: F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_merge_sort_impl(A, grain, max_depth(), &|a: &T, b: &T| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+pub fn par_merge_sort_by_key<T, K, F>(A: &mut [T], grain: usize, f: F)
+where
+    T: Clone + Send + Sync,
+    K: PartialOrd,
+    F: Fn(&T) -> K + Sync,
+{
+    par_merge_sort_impl(A, grain, max_depth(), &|a: &T, b: &T| f(a) < f(b));
+}
+
+mod tests {
+    #[test]
+    fn par_merge_sort() {
+        use super::par_merge_sort;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(100_000);
+        par_merge_sort(A.as_mut_slice(), 1000);
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec::<i32>(100);
+        par_merge_sort(A.as_mut_slice(), 1);
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn par_merge_sort_threads() {
+        use super::par_merge_sort_impl;
+        use crate::common;
+        let mut A = common::random_vec::<u64>(100_000);
+        let n_threads = par_merge_sort_impl(
+            A.as_mut_slice(),
+            16,
+            3,
+            &|a: &u64, b: &u64| a < b,
+        );
+        common::assert_asc(&A);
+        assert!(0 < n_threads && n_threads < 1 << 3);
+    }
+
+    #[test]
+    fn par_merge_sort_by() {
+        use super::par_merge_sort_by;
+        use crate::common;
+        let mut A = common::random_vec::<i32>(10_000);
+        par_merge_sort_by(A.as_mut_slice(), 500, |a, b| b.cmp(a));
+        common::assert_desc(&A);
+    }
+
+    #[test]
+    fn par_merge_sort_by_key_stable() {
+        use super::par_merge_sort_by_key;
+        use crate::common;
+        let mut A = common::random_records(10_000, 10);
+        par_merge_sort_by_key(A.as_mut_slice(), 100, |r| r.0);
+        common::assert_stable(&A);
+    }
+}
@@ -0,0 +1,168 @@
+use super::max_depth;
+use crate::ch07_Quicksort::{
+    partition_step,
+    quick_sort_impl,
+    PartitionBy,
+};
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+use std::thread;
+
+// Every split spawns a thread for its smaller side and keeps the larger one,
+// so `depth` splits give at most 2^depth - 1 threads.  Past that, or at
+// `grain` elements, the rest is sorted sequentially, which keeps bad pivots
+// from piling up threads.  Returns the number of threads spawned.
+fn par_quick_sort_impl<T, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    grain: usize,
+    depth: usize,
+    is_less: &F,
+) -> usize
+where
+    T: Send,
+    F: Fn(&T, &T) -> bool + Sync,
+{
+    if A.len() <= 1 || A.len() <= grain || depth == 0 {
+        quick_sort_impl(A, partition_type, &mut |a: &T, b: &T| is_less(a, b));
+        return 0;
+    }
+
+    let (lt, gt) =
+        partition_step(A, partition_type, &mut |a: &T, b: &T| is_less(a, b));
+    let (left, right) = A.split_at_mut(lt);
+    let right = &mut right[gt - lt..];
+    let (smaller, larger) = if left.len() < right.len() {
+        (left, right)
+    } else {
+        (right, left)
+    };
+
+    thread::scope(|s| {
+        let spawned = s.spawn(|| {
+            par_quick_sort_impl(
+                smaller,
+                partition_type,
+                grain,
+                depth - 1,
+                is_less,
+            )
+        });
+        let n_threads = par_quick_sort_impl(
+            larger,
+            partition_type,
+            grain,
+            depth - 1,
+            is_less,
+        );
+        1 + n_threads + spawned.join().unwrap()
+    })
+}
+
+pub fn par_quick_sort<T>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    grain: usize,
+) where
+    T: PartialOrd + Send,
+{
+    par_quick_sort_impl(
+        A,
+        partition_type,
+        grain,
+        max_depth(),
+        &|a: &T, b: &T| a < b,
+    );
+}
+
+pub fn par_quick_sort_by<T, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    grain: usize,
+    compare: F,
+) where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quick_sort_impl(
+        A,
+        partition_type,
+        grain,
+        max_depth(),
+        &|a: &T, b: &T| compare(a, b) == Ordering::Less,
+    );
+}
+
+pub fn par_quick_sort_by_key<T, K, F>(
+    A: &mut [T],
+    partition_type: PartitionBy<T>,
+    grain: usize,
+    f: F,
+) where
+    T: Send,
+    K: PartialOrd,
+    F: Fn(&T) -> K + Sync,
+{
+    par_quick_sort_impl(
+        A,
+        partition_type,
+        grain,
+        max_depth(),
+        &|a: &T, b: &T| f(a) < f(b),
+    );
+}
+
+mod tests {
+    #[test]
+    fn par_quick_sort() {
+        use super::par_quick_sort;
+        use crate::ch07_Quicksort::PartitionBy;
+        use crate::common;
+        let mut A = common::random_vec::<f64>(100_000);
+        par_quick_sort(A.as_mut_slice(), PartitionBy::Random, 1000);
+        common::assert_asc(&A);
+
+        let mut A = common::random_vec_range::<u8>(100_000, 0, 4);
+        par_quick_sort(A.as_mut_slice(), PartitionBy::ThreeWay, 1000);
+        common::assert_asc(&A);
+
+        let mut A: Vec<u32> = (0..100_000).collect();
+        par_quick_sort(A.as_mut_slice(), PartitionBy::Ninther, 1000);
+        common::assert_asc(&A);
+    }
+
+    #[test]
+    fn par_quick_sort_presorted() {
+        use super::par_quick_sort_impl;
+        use crate::ch07_Quicksort::PartitionBy;
+        use crate::common;
+        let mut A: Vec<i64> = (0..5_000).collect();
+        let n_threads = par_quick_sort_impl(
+            A.as_mut_slice(),
+            PartitionBy::Last,
+            16,
+            4,
+            &|a: &i64, b: &i64| a < b,
+        );
+        common::assert_asc(&A);
+        assert!(n_threads < 1 << 4);
+    }
+
+    #[test]
+    fn par_quick_sort_by_key() {
+        use super::par_quick_sort_by_key;
+        use crate::ch07_Quicksort::PartitionBy;
+        use crate::common;
+        let mut A = common::random_records(10_000, 100);
+        par_quick_sort_by_key(
+            A.as_mut_slice(),
+            PartitionBy::Random,
+            100,
+            |r| r.0,
+        );
+        let keys: Vec<_> = A.iter().map(|r| r.0).collect();
+        common::assert_asc(&keys);
+    }
+}
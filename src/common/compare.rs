@@ -0,0 +1,30 @@
+use std::cmp::{
+    Ordering,
+    PartialOrd,
+};
+
+// Every comparison sort in this crate is written against an `is_less`
+// predicate, so that the plain, `_by` and `_by_key` flavours share one
+// implementation.
+
+#[inline]
+pub fn by_partial_ord<T: PartialOrd>() -> impl FnMut(&T, &T) -> bool {
+    |a: &T, b: &T| a < b
+}
+
+#[inline]
+pub fn by_ordering<T, F>(mut compare: F) -> impl FnMut(&T, &T) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    move |a: &T, b: &T| compare(a, b) == Ordering::Less
+}
+
+#[inline]
+pub fn by_key<T, K, F>(mut f: F) -> impl FnMut(&T, &T) -> bool
+where
+    K: PartialOrd,
+    F: FnMut(&T) -> K,
+{
+    move |a: &T, b: &T| f(a) < f(b)
+}
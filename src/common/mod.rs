@@ -1,5 +1,8 @@
 extern crate rand;
 
+pub mod compare;
+pub mod sorter;
+
 use rand::distributions::{
     Distribution,
     Uniform,
@@ -34,3 +37,33 @@ where
 
     (0..n).map(|_| dist.sample(&mut rng)).collect()
 }
+
+pub fn random_records(n: usize, n_keys: u32) -> Vec<(u32, usize)> {
+    random_vec_range::<u32>(n, 0, n_keys)
+        .into_iter()
+        .enumerate()
+        .map(|(i, key)| (key, i))
+        .collect()
+}
+
+pub fn assert_stable(arr: &[(u32, usize)]) {
+    for i in 0..arr.len() - 1 {
+        assert!(
+            arr[i].0 < arr[i + 1].0
+                || arr[i].0 == arr[i + 1].0 && arr[i].1 < arr[i + 1].1
+        )
+    }
+}
+
+// O(n^2) oracle for the number of pairs `i < j` with `arr[i] > arr[j]`.
+pub fn brute_force_inversions<T: PartialOrd>(arr: &[T]) -> u64 {
+    let mut inversions = 0;
+    for i in 0..arr.len() {
+        for j in i + 1..arr.len() {
+            if arr[j] < arr[i] {
+                inversions += 1;
+            }
+        }
+    }
+    inversions
+}
@@ -0,0 +1,609 @@
+use crate::{
+    ch02_Getting_Start::{
+        adaptive_merge_sort::adaptive_merge_sort,
+        bubble_sort::bubble_sort,
+        insertion_sort::insertion_sort,
+        merge_sort::{
+            in_place_merge_sort,
+            merge_sort,
+            merge_sort_bottom_up,
+        },
+    },
+    ch06_Heapsort::{
+        heap_sort,
+        heap_sort_bottom_up,
+        young_tableau::young_sort,
+    },
+    ch07_Quicksort::{
+        dual_pivot_quick_sort::dual_pivot_quick_sort,
+        intro_sort::intro_sort,
+        quick_sort,
+        PartitionBy,
+    },
+    ch08_Sorting_in_Linear_Time::{
+        bucket_sort::bucket_sort,
+        counting_sort::{
+            counting_sort_auto,
+            CountingKey,
+        },
+        msd_radix_sort::{
+            american_flag_sort,
+            msd_radix_sort,
+        },
+        radix_sort::{
+            radix_sort,
+            Radix,
+        },
+    },
+    ch27_Multithreaded_Algorithms::{
+        par_merge_sort::par_merge_sort,
+        par_quick_sort::par_quick_sort,
+        DEFAULT_GRAIN_SIZE,
+    },
+};
+use std::cmp::PartialOrd;
+
+pub trait Sorter<T> {
+    fn name(&self) -> &'static str;
+    fn is_stable(&self) -> bool;
+    fn is_in_place(&self) -> bool;
+    fn sort(&self, A: &mut [T]);
+}
+
+pub struct BubbleSort;
+pub struct InsertionSort;
+pub struct MergeSort;
+pub struct MergeSortBottomUp;
+pub struct AdaptiveMergeSort;
+pub struct InPlaceMergeSort;
+pub struct HeapSort;
+pub struct HeapSortBottomUp;
+pub struct YoungSort;
+pub struct QuickSort<T>(pub PartitionBy<T>);
+pub struct IntroSort;
+pub struct DualPivotQuickSort;
+pub struct ParMergeSort;
+pub struct ParQuickSort;
+pub struct CountingSort;
+pub struct RadixSort;
+pub struct MsdRadixSort;
+pub struct AmericanFlagSort;
+pub struct BucketSort;
+
+impl<T: PartialOrd> Sorter<T> for BubbleSort {
+    fn name(&self) -> &'static str {
+        "bubble_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        bubble_sort(A)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for InsertionSort {
+    fn name(&self) -> &'static str {
+        "insertion_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        insertion_sort(A)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for MergeSort {
+    fn name(&self) -> &'static str {
+        "merge_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        merge_sort(A)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for MergeSortBottomUp {
+    fn name(&self) -> &'static str {
+        "merge_sort (bottom-up)"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        merge_sort_bottom_up(A)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for AdaptiveMergeSort {
+    fn name(&self) -> &'static str {
+        "adaptive_merge_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        adaptive_merge_sort(A)
+    }
+}
+
+impl<T: PartialOrd> Sorter<T> for InPlaceMergeSort {
+    fn name(&self) -> &'static str {
+        "in_place_merge_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        in_place_merge_sort(A)
+    }
+}
+
+impl<T: PartialOrd> Sorter<T> for HeapSort {
+    fn name(&self) -> &'static str {
+        "heap_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        heap_sort(A)
+    }
+}
+
+impl<T: PartialOrd> Sorter<T> for HeapSortBottomUp {
+    fn name(&self) -> &'static str {
+        "heap_sort (bottom-up)"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        heap_sort_bottom_up(A)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for YoungSort {
+    fn name(&self) -> &'static str {
+        "young_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        young_sort(A)
+    }
+}
+
+impl<T: PartialOrd> Sorter<T> for QuickSort<T> {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            PartitionBy::Last => "quick_sort (last)",
+            PartitionBy::Random => "quick_sort (random)",
+            PartitionBy::ThreeWay => "quick_sort (three-way)",
+            PartitionBy::MedianOfThree => "quick_sort (median-of-three)",
+            PartitionBy::Ninther => "quick_sort (ninther)",
+            PartitionBy::Custom(_) => "quick_sort (custom)",
+        }
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        quick_sort(A, self.0)
+    }
+}
+
+impl<T: PartialOrd + Clone> Sorter<T> for IntroSort {
+    fn name(&self) -> &'static str {
+        "intro_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        intro_sort(A)
+    }
+}
+
+impl<T: PartialOrd> Sorter<T> for DualPivotQuickSort {
+    fn name(&self) -> &'static str {
+        "dual_pivot_quick_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        dual_pivot_quick_sort(A)
+    }
+}
+
+impl<T: PartialOrd + Clone + Send + Sync> Sorter<T> for ParMergeSort {
+    fn name(&self) -> &'static str {
+        "par_merge_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        par_merge_sort(A, DEFAULT_GRAIN_SIZE)
+    }
+}
+
+impl<T: PartialOrd + Send> Sorter<T> for ParQuickSort {
+    fn name(&self) -> &'static str {
+        "par_quick_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        par_quick_sort(A, PartitionBy::Random, DEFAULT_GRAIN_SIZE)
+    }
+}
+
+impl<T: CountingKey> Sorter<T> for CountingSort {
+    fn name(&self) -> &'static str {
+        "counting_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        counting_sort_auto(A)
+    }
+}
+
+impl<T: Radix> Sorter<T> for RadixSort {
+    fn name(&self) -> &'static str {
+        "radix_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        radix_sort(A)
+    }
+}
+
+impl<T: AsRef<[u8]>> Sorter<T> for MsdRadixSort {
+    fn name(&self) -> &'static str {
+        "msd_radix_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        msd_radix_sort(A)
+    }
+}
+
+impl<T: AsRef<[u8]>> Sorter<T> for AmericanFlagSort {
+    fn name(&self) -> &'static str {
+        "american_flag_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        false
+    }
+
+    fn is_in_place(&self) -> bool {
+        true
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        american_flag_sort(A)
+    }
+}
+
+// `Sorter::sort` cannot report errors, so this panics on a NaN key where
+// `bucket_sort` returns `BucketSortError::NaNKey`.  Call `bucket_sort`
+// directly for keys that may be NaN.
+impl<T: Copy + Into<f64>> Sorter<T> for BucketSort {
+    fn name(&self) -> &'static str {
+        "bucket_sort"
+    }
+
+    fn is_stable(&self) -> bool {
+        true
+    }
+
+    fn is_in_place(&self) -> bool {
+        false
+    }
+
+    fn sort(&self, A: &mut [T]) {
+        bucket_sort(A).expect("bucket sort keys must not be NaN")
+    }
+}
+
+pub fn comparison_sorters<T>() -> Vec<Box<dyn Sorter<T>>>
+where
+    T: PartialOrd + Clone + 'static,
+{
+    vec![
+        Box::new(BubbleSort),
+        Box::new(InsertionSort),
+        Box::new(MergeSort),
+        Box::new(MergeSortBottomUp),
+        Box::new(AdaptiveMergeSort),
+        Box::new(InPlaceMergeSort),
+        Box::new(HeapSort),
+        Box::new(HeapSortBottomUp),
+        Box::new(YoungSort),
+        Box::new(QuickSort(PartitionBy::Last)),
+        Box::new(QuickSort(PartitionBy::Random)),
+        Box::new(QuickSort(PartitionBy::ThreeWay)),
+        Box::new(QuickSort(PartitionBy::MedianOfThree)),
+        Box::new(QuickSort(PartitionBy::Ninther)),
+        Box::new(IntroSort),
+        Box::new(DualPivotQuickSort),
+    ]
+}
+
+pub fn parallel_sorters<T>() -> Vec<Box<dyn Sorter<T>>>
+where
+    T: PartialOrd + Clone + Send + Sync + 'static,
+{
+    vec![Box::new(ParMergeSort), Box::new(ParQuickSort)]
+}
+
+pub trait Registry: Sized {
+    fn sorters() -> Vec<Box<dyn Sorter<Self>>>;
+}
+
+pub fn sorters<T: Registry>() -> Vec<Box<dyn Sorter<T>>> {
+    T::sorters()
+}
+
+macro_rules! impl_registry_integer {
+    ($t:ty) => {
+        impl Registry for $t {
+            fn sorters() -> Vec<Box<dyn Sorter<$t>>> {
+                let mut sorters = comparison_sorters();
+                sorters.extend(parallel_sorters());
+                sorters.push(Box::new(RadixSort));
+                sorters
+            }
+        }
+    };
+    // Counting sort only pays off where the key range is small, although it
+    // falls back to merge sort for ranges much wider than the input.
+    ($t:ty, counting_sort) => {
+        impl Registry for $t {
+            fn sorters() -> Vec<Box<dyn Sorter<$t>>> {
+                let mut sorters = comparison_sorters();
+                sorters.extend(parallel_sorters());
+                sorters.push(Box::new(RadixSort));
+                sorters.push(Box::new(CountingSort));
+                sorters
+            }
+        }
+    };
+}
+
+// Bucket sort panics on NaN, so float inputs given to the registered sorters
+// must not hold any.
+macro_rules! impl_registry_float {
+    ($t:ty) => {
+        impl Registry for $t {
+            fn sorters() -> Vec<Box<dyn Sorter<$t>>> {
+                let mut sorters = comparison_sorters();
+                sorters.extend(parallel_sorters());
+                sorters.push(Box::new(RadixSort));
+                sorters.push(Box::new(BucketSort));
+                sorters
+            }
+        }
+    };
+}
+
+impl_registry_integer!(u8, counting_sort);
+impl_registry_integer!(u16, counting_sort);
+impl_registry_integer!(u32);
+impl_registry_integer!(u64);
+impl_registry_integer!(usize);
+
+impl_registry_integer!(i8, counting_sort);
+impl_registry_integer!(i16, counting_sort);
+impl_registry_integer!(i32, counting_sort);
+impl_registry_integer!(i64);
+impl_registry_integer!(isize);
+
+impl_registry_float!(f32);
+impl_registry_float!(f64);
+
+impl Registry for String {
+    fn sorters() -> Vec<Box<dyn Sorter<String>>> {
+        let mut sorters = comparison_sorters();
+        sorters.extend(parallel_sorters());
+        sorters.push(Box::new(MsdRadixSort));
+        sorters.push(Box::new(AmericanFlagSort));
+        sorters
+    }
+}
+
+mod tests {
+    use std::cmp::Ordering;
+
+    #[derive(Clone, Debug)]
+    struct Record(u32, usize);
+
+    impl PartialEq for Record {
+        fn eq(&self, other: &Record) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl PartialOrd for Record {
+        fn partial_cmp(&self, other: &Record) -> Option<Ordering> {
+            self.0.partial_cmp(&other.0)
+        }
+    }
+
+    macro_rules! test_sorters {
+        ($t:ty, $A:expr) => {
+            for sorter in sorters::<$t>() {
+                let mut A = $A.clone();
+                sorter.sort(A.as_mut_slice());
+                common::assert_asc(&A);
+            }
+        };
+    }
+
+    #[test]
+    fn registry() {
+        use super::sorters;
+        use crate::common;
+
+        test_sorters!(i32, common::random_vec_range::<i32>(200, -500, 500));
+        test_sorters!(i64, common::random_vec::<i64>(200));
+        test_sorters!(u8, common::random_vec::<u8>(200));
+        test_sorters!(u64, common::random_vec::<u64>(200));
+        test_sorters!(f64, common::random_vec::<f64>(200));
+        test_sorters!(f32, common::random_vec::<f32>(200));
+        test_sorters!(
+            String,
+            common::random_vec::<u64>(200)
+                .into_iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn registry_short_input() {
+        use super::sorters;
+
+        for sorter in sorters::<i32>() {
+            let mut A: Vec<i32> = vec![];
+            sorter.sort(A.as_mut_slice());
+            let mut A = vec![42];
+            sorter.sort(A.as_mut_slice());
+            assert_eq!(A, vec![42]);
+        }
+    }
+
+    #[test]
+    fn comparison_sorters_stability() {
+        use super::comparison_sorters;
+        use crate::common;
+
+        let records = common::random_records(200, 10);
+        for sorter in comparison_sorters::<Record>() {
+            if !sorter.is_stable() {
+                continue;
+            }
+            let mut A: Vec<_> =
+                records.iter().map(|&(key, i)| Record(key, i)).collect();
+            sorter.sort(A.as_mut_slice());
+            let A: Vec<_> = A.into_iter().map(|r| (r.0, r.1)).collect();
+            common::assert_stable(&A);
+        }
+    }
+}
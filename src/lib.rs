@@ -10,3 +10,4 @@ pub mod ch08_Sorting_in_Linear_Time;
 pub mod ch09_Medians_and_Order_Statistics;
 pub mod ch10_Elementary_Data_Structures;
 pub mod ch12_Binary_Search_Trees;
+pub mod ch27_Multithreaded_Algorithms;